@@ -0,0 +1,174 @@
+#![allow(incomplete_features)] // reason: generic_const_exprs
+#![feature(generic_const_exprs)]
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use nalgebra::Vector2;
+use term_gfx_rs::graphics::{
+    Attenuation, BufferedCanvas, Bvh, Camera, Canvas, Colour, DirectionalLight, Hit, LightColour,
+    Material, PointLight, Ray, Shape, Sphere, World, WorldVector,
+};
+
+/// Sphere counts scenes are benchmarked with, into the
+/// thousands to show how the bounding volume hierarchy scales
+const SCENE_SIZES: [usize; 4] = [4, 64, 1024, 4096];
+
+/// The `i`th sphere of a grid `columns` spheres wide
+fn grid_sphere(i: usize, columns: usize) -> Sphere {
+    Sphere {
+        center: WorldVector::new(
+            (i % columns) as f64 * 3.0 - columns as f64 * 1.5,
+            (i / columns) as f64 * 3.0 - columns as f64 * 1.5,
+            20.0 + (i % 3) as f64 * 5.0,
+        ),
+        radius: 1.0,
+        material: Material {
+            colour: LightColour::new(1.0, 0.5, 0.25),
+            specular: Some(100.0),
+            reflective: 0.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
+        },
+    }
+}
+
+/// A grid of `spheres` spheres in front of the camera,
+/// lit by a point and a directional light
+fn scene(spheres: usize) -> World {
+    let columns = (spheres as f64).sqrt().ceil() as usize;
+    World::new(
+        (0..spheres)
+            .map(|i| Box::new(grid_sphere(i, columns)) as Box<dyn Shape>)
+            .collect(),
+        vec![
+            Box::new(PointLight {
+                position: WorldVector::zeros(),
+                colour: LightColour::from_element(0.5),
+                attenuation: Attenuation::NONE,
+            }),
+            Box::new(DirectionalLight {
+                direction: WorldVector::new(-1.0, -1.0, 1.0).normalize(),
+                colour: LightColour::from_element(1.0),
+            }),
+        ],
+        Camera::new(Vector2::new(80, 48)),
+        LightColour::from_element(0.3),
+    )
+}
+
+fn sphere_intersect(c: &mut Criterion) {
+    let sphere = Sphere {
+        center: WorldVector::new(0.0, 0.0, 10.0),
+        radius: 5.0,
+        material: Material {
+            colour: LightColour::x(),
+            specular: None,
+            reflective: 0.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
+        },
+    };
+    let mut group = c.benchmark_group("Sphere::intersect");
+    for (name, to) in [
+        ("hit", WorldVector::new(0.0, 0.0, 1.0)),
+        ("miss", WorldVector::new(0.0, 1.0, 0.0)),
+    ] {
+        let ray = Ray::new(WorldVector::zeros(), to);
+        group.bench_function(name, |b| {
+            b.iter(|| black_box(&sphere).intersect(black_box(&ray)))
+        });
+    }
+    group.finish();
+}
+
+fn closest_intersection(c: &mut Criterion) {
+    let mut group = c.benchmark_group("World::closest_intersection");
+    for spheres in SCENE_SIZES {
+        let world = scene(spheres);
+        let ray = Ray::new(WorldVector::zeros(), WorldVector::new(0.05, 0.05, 1.0));
+        group.bench_with_input(BenchmarkId::from_parameter(spheres), &world, |b, world| {
+            b.iter(|| world.closest_intersection(black_box(&ray), 1.0, 10000.0))
+        });
+    }
+    group.finish();
+}
+
+fn get_lighting(c: &mut Criterion) {
+    let mut group = c.benchmark_group("World::get_lighting");
+    for spheres in SCENE_SIZES {
+        let world = scene(spheres);
+        // the point of the first sphere facing the camera
+        let sphere = grid_sphere(0, (spheres as f64).sqrt().ceil() as usize);
+        let normal = -sphere.center.normalize();
+        let hit = Hit {
+            point: sphere.center + normal * sphere.radius,
+            normal,
+            direction: normal,
+            material: sphere.material.clone(),
+        };
+        group.bench_with_input(BenchmarkId::from_parameter(spheres), &world, |b, world| {
+            b.iter(|| world.get_lighting(black_box(&hit)))
+        });
+    }
+    group.finish();
+}
+
+fn bvh_build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Bvh::build");
+    for spheres in SCENE_SIZES {
+        let columns = (spheres as f64).sqrt().ceil() as usize;
+        let bounds = (0..spheres)
+            .map(|i| grid_sphere(i, columns).bounds())
+            .collect::<Vec<_>>();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(spheres),
+            &bounds,
+            |b, bounds| b.iter(|| Bvh::build(black_box(bounds))),
+        );
+    }
+    group.finish();
+}
+
+/// Benchmarks filling and displaying a `BufferedCanvas` of each size
+macro_rules! buffered_canvas {
+    ($c:expr, $(($width:literal, $height:literal)),+) => {{
+        let mut put_cell = $c.benchmark_group("BufferedCanvas::put_cell");
+        $(
+            let mut canvas = BufferedCanvas::<$width, $height, 1>::new();
+            put_cell.bench_function(format!("{}x{}", $width, $height), |b| {
+                b.iter(|| {
+                    for y in 0..$height {
+                        for x in 0..$width {
+                            canvas.put_cell(*b"  ", black_box(Colour::new(255, 128, 0)), Vector2::new(x, y));
+                        }
+                    }
+                })
+            });
+        )+
+        put_cell.finish();
+
+        let mut display = $c.benchmark_group("BufferedCanvas::display");
+        $(
+            let mut canvas = BufferedCanvas::<$width, $height, 1>::new();
+            display.bench_function(format!("{}x{}", $width, $height), |b| {
+                b.iter(|| canvas.display(&mut std::io::sink()))
+            });
+        )+
+        display.finish();
+    }};
+}
+
+fn buffered_canvas(c: &mut Criterion) {
+    buffered_canvas!(c, (20, 20), (80, 48), (160, 90));
+}
+
+criterion_group!(
+    benches,
+    sphere_intersect,
+    closest_intersection,
+    get_lighting,
+    bvh_build,
+    buffered_canvas
+);
+criterion_main!(benches);
@@ -0,0 +1,186 @@
+use std::{f64::consts::TAU, io, time::Instant};
+
+use nalgebra::Vector2;
+
+use crate::graphics::{
+    Ascii, Blocks, Braille, Canvas, Colour, HalfBlocks, MemoryCanvas, Quadrants, Renderer,
+    TerminalCanvas, World, WorldVector,
+};
+
+use super::{
+    add_model, argument, demo_world, renderer, statistics::Summary, Application, FrameTime,
+};
+
+/// Runs the demo scene with a scripted camera for a fixed number of
+/// frames, without input or terminal output, then reports frame timing.
+/// Displayed frames are written to a sink so only encoding is measured.
+pub struct BenchmarkApp<'a> {
+    canvas: Box<dyn Canvas>,
+    /// Name of the canvas, for the report
+    canvas_name: String,
+    renderer: Box<dyn Renderer>,
+    world: World,
+    title: &'a str,
+
+    /// Frames to run for
+    frames: usize,
+    frame: usize,
+    frame_time: FrameTime,
+    /// Update, render and total time of each frame
+    samples: Vec<[f64; 3]>,
+}
+
+impl<'a> BenchmarkApp<'a> {
+    /// Frames run when `--frames` isn't given
+    const DEFAULT_FRAMES: usize = 300;
+    /// Terminal size canvases are created for, columns by rows
+    const TERMINAL_SIZE: Vector2<u16> = Vector2::new(160, 48);
+
+    /// Creates a canvas by name, `memory` or a terminal canvas
+    /// drawn with the cell mode of the same name
+    fn canvas(name: &str) -> Option<Box<dyn Canvas>> {
+        let size = Self::TERMINAL_SIZE;
+        Some(match name {
+            // the same pixels as a terminal canvas of blocks
+            "memory" => Box::new(MemoryCanvas::new(Vector2::new(
+                size.x as usize / 2,
+                size.y as usize,
+            ))),
+            "blocks" => Box::new(TerminalCanvas::with_size(Blocks, size)),
+            "half-blocks" => Box::new(TerminalCanvas::with_size(HalfBlocks, size)),
+            "quadrants" => Box::new(TerminalCanvas::with_size(Quadrants, size)),
+            "braille" => Box::new(TerminalCanvas::with_size(Braille, size)),
+            "ascii" => Box::new(TerminalCanvas::with_size(Ascii::default(), size)),
+            _ => return None,
+        })
+    }
+
+    /// Moves the camera along its path, a full turn about the
+    /// origin while bobbing up and down, by how far through the run it is
+    fn script_camera(&mut self) {
+        let progress = self.frame as f64 / self.frames as f64;
+        let camera = &mut self.world.camera;
+        camera.position = WorldVector::new(0.0, 2.0 * (progress * TAU).sin(), 0.0);
+        camera.look(progress * TAU, 0.2 * (2.0 * progress * TAU).sin());
+    }
+
+    /// Writes the timing statistics, in milliseconds
+    pub fn report(&self, out: &mut dyn io::Write) -> Result<(), io::Error> {
+        writeln!(
+            out,
+            "{}: {} frames, {}x{} {} canvas, {}",
+            self.title,
+            self.samples.len(),
+            self.canvas.size().x,
+            self.canvas.size().y,
+            self.canvas_name,
+            self.renderer.name()
+        )?;
+        writeln!(
+            out,
+            "{:>8} {:>9} {:>9} {:>9} {:>9}",
+            "(ms)", "min", "mean", "p95", "max"
+        )?;
+        for (i, label) in ["UPDATE", "RENDER", "FRAME"].iter().enumerate() {
+            let timings = self.samples.iter().map(|s| s[i]).collect::<Vec<_>>();
+            let summary = Summary::of(&timings);
+            writeln!(
+                out,
+                "{:>8} {:>9.3} {:>9.3} {:>9.3} {:>9.3}",
+                label, summary.min, summary.mean, summary.p95, summary.max
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Application<'a> for BenchmarkApp<'a> {
+    type Error = io::Error;
+
+    fn fresh(title: &'a str) -> Self {
+        // `--canvas memory|blocks|half-blocks|quadrants|braille|ascii`
+        let canvas_name = argument("--canvas")
+            .filter(|name| Self::canvas(name).is_some())
+            .unwrap_or_else(|| "memory".to_string());
+        let canvas = Self::canvas(&canvas_name).unwrap();
+        let world = demo_world(canvas.as_ref());
+        Self {
+            canvas,
+            canvas_name,
+            renderer: renderer(),
+            world,
+            title,
+            // `--frames <count>`
+            frames: argument("--frames")
+                .and_then(|frames| frames.parse().ok())
+                .filter(|&frames| frames > 0)
+                .unwrap_or(Self::DEFAULT_FRAMES),
+            frame: 0,
+            frame_time: FrameTime {
+                start: Instant::now(),
+                render: 0.0,
+                update: 0.0,
+                total: 0.0,
+            },
+            samples: Vec::new(),
+        }
+    }
+
+    fn initialise(&mut self) -> Result<(), Self::Error> {
+        self.samples.reserve(self.frames);
+        add_model(&mut self.world)
+    }
+
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        self.canvas.clear();
+        Ok(())
+    }
+
+    fn begin_frame(&mut self) -> Result<(), Self::Error> {
+        self.frame_time.start = Instant::now();
+        Ok(())
+    }
+
+    fn input(&mut self) -> Result<(), Self::Error> {
+        self.script_camera();
+        Ok(())
+    }
+
+    fn update(&mut self) -> Result<(), Self::Error> {
+        let now = Instant::now();
+        self.renderer.render(&self.world, self.canvas.as_mut());
+        self.canvas.write(
+            format!("FRAME {:>5}", self.frame),
+            Colour::from_element(40),
+            Vector2::zeros(),
+        );
+        self.frame_time.update = now.elapsed().as_millis_f64();
+        Ok(())
+    }
+
+    fn render(&mut self) -> Result<(), Self::Error> {
+        let now = Instant::now();
+        self.canvas.display(&mut io::sink())?;
+        self.frame_time.render = now.elapsed().as_millis_f64();
+        Ok(())
+    }
+
+    fn end_frame(&mut self) -> Result<(), Self::Error> {
+        self.frame_time.total = self.frame_time.start.elapsed().as_millis_f64();
+        self.samples.push([
+            self.frame_time.update,
+            self.frame_time.render,
+            self.frame_time.total,
+        ]);
+        self.frame += 1;
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result<(), Self::Error> {
+        self.report(&mut io::stdout().lock())
+    }
+
+    fn is_running(&self) -> bool {
+        self.frame < self.frames
+    }
+}
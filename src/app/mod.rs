@@ -1,21 +1,30 @@
+pub mod benchmark_app;
+pub mod recording;
 pub mod state;
-// TODO: move App into app.rs 
-// TODO: make benchmark_app.rs - BenchmarkApp implements Application
+pub mod statistics;
+pub mod synchronized;
+mod test;
+// TODO: move App into app.rs
 use std::{
     cmp::Ordering,
     error::Error,
-    io::{self, BufWriter, StdoutLock, Write},
-    time::{Duration, Instant},
+    fs::File,
+    io::{self, BufWriter, IsTerminal, Write},
+    path::Path,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use crate::graphics::{
-    BufferedCanvas, Camera, Canvas, Colour, DirectionalLight, LightColour, LightingContribution,
-    Material, PointLight, Sphere, World, WorldVector,
+    export, load_obj, Attenuation, Blocks, Camera, Canvas, CellMode, Colour, ColourDepth,
+    DirectionalLight, Dither, ImageFormat, LightColour, LightSource, LightingContribution,
+    Material, Mesh, Plane, PointLight, Renderer, Sphere, SphereLight, SwapChain, TerminalCanvas,
+    Tracer, World, WorldVector,
 };
 use crossterm::{
     cursor,
+    event::{self as terminal_event, Event as TerminalEvent},
     terminal::{
-        disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+        self, disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
         LeaveAlternateScreen, SetTitle,
     },
     ExecutableCommand,
@@ -23,6 +32,7 @@ use crossterm::{
 use mouse_position::mouse_position::Mouse;
 use nalgebra::{Vector2, Vector3};
 use num_traits::{clamp, Zero};
+use recording::Recorder;
 use state::{Event, State};
 fn set_mouse_pos(x: i32, y: i32) {
     unsafe {
@@ -77,7 +87,6 @@ impl Calibration {
 }
 
 pub trait Application<'a> {
-    
     type Error: Error;
 
     /// Returns a new and prepared application
@@ -87,22 +96,22 @@ pub trait Application<'a> {
     /// Initialises the application ready
     /// for rendering
     fn initialise(&mut self) -> Result<(), Self::Error>;
-    
+
     /// Clears the canvas and resets the cursor
     fn clear(&mut self) -> Result<(), Self::Error>;
-    
+
     /// Called at the start of the frame, after Self::clear
     fn begin_frame(&mut self) -> Result<(), Self::Error>;
 
     /// Handles input, before Self::update
     fn input(&mut self) -> Result<(), Self::Error>;
-    
+
     /// Update logic for the application, runs before Self::render
     fn update(&mut self) -> Result<(), Self::Error>;
-    
+
     /// Renders the application
     fn render(&mut self) -> Result<(), Self::Error>;
-    
+
     /// Called at the end of the frame, after Self::render
     fn end_frame(&mut self) -> Result<(), Self::Error>;
 
@@ -113,18 +122,175 @@ pub trait Application<'a> {
     fn is_running(&self) -> bool;
 }
 
-/// Writes to StdoutLock with a BufWriter
-/// Uses a lock to avoid locking and unlocking,
-/// is a BufWriter to allow faster prints, avoiding
-/// buffering on new-lines (we print a lot of those).
-pub type StdoutWriter<'a> = BufWriter<StdoutLock<'a>>;
+/// Returns the value following the command line flag `name`
+pub fn argument(name: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != name).nth(1)
+}
 
-pub struct App<'a> {
-    /// Used for writing to Stdout
-    _buf_writer: StdoutWriter<'a>,
-    canvas: BufferedCanvas<'a, 200, 100, 1>,
+/// Creates the renderer, `--threads <count>` sets the threads traced
+/// on, otherwise a thread per core, and `--reflections <depth>` how
+/// many times reflected and refracted rays are followed
+pub fn renderer() -> Box<dyn Renderer> {
+    let mut tracer = argument("--threads")
+        .and_then(|threads| threads.parse().ok())
+        .filter(|&threads| threads > 0)
+        .map_or_else(Tracer::available, Tracer::new);
+    if let Some(depth) = argument("--reflections").and_then(|depth| depth.parse().ok()) {
+        tracer.set_reflection_depth(depth);
+    }
+    Box::new(tracer)
+}
+
+/// Whether the command line flag `name` was passed
+pub fn flag(name: &str) -> bool {
+    std::env::args().any(|arg| arg == name)
+}
+
+/// The demo scene, four spheres and a glass ball around the origin
+/// above a floor, lit by a point and a directional light. The camera
+/// is fitted to `canvas`.
+pub fn demo_world(canvas: &dyn Canvas) -> World {
+    let mut camera = Camera::new(canvas.size());
+    camera.set_pixel_aspect(canvas.pixel_aspect(), canvas.size());
+    // `--shadow-samples <count>` swaps the point light for a round light
+    // overhead, its shadows are soft but each sample is another shadow
+    // ray at every hit, so they're off by default
+    let light: Box<dyn LightSource> = match argument("--shadow-samples")
+        .and_then(|samples| samples.parse().ok())
+        .filter(|&samples| samples > 0)
+    {
+        Some(samples) => Box::new(SphereLight {
+            center: Vector3::new(0.0, 14.0, 2.0),
+            radius: 2.0,
+            colour: LightColour::from_element(0.5),
+            samples,
+        }),
+        None => Box::new(PointLight {
+            position: Vector3::zeros(),
+            colour: LightColour::from_element(0.5),
+            attenuation: Attenuation::NONE,
+        }),
+    };
+    World::new(
+        vec![
+            Box::new(Sphere {
+                center: Vector3::new(0.0, 6.0, 10.0),
+                radius: 5.0,
+                material: Material {
+                    colour: LightColour::x(),
+                    specular: Some(500.0),
+                    reflective: 0.3,
+                    transparency: 0.0,
+                    refractive_index: 1.0,
+                },
+            }),
+            Box::new(Sphere {
+                center: Vector3::new(10.0, 6.0, 0.0),
+                radius: 5.0,
+                material: Material {
+                    colour: LightColour::z(),
+                    specular: Some(500.0),
+                    reflective: 0.3,
+                    transparency: 0.0,
+                    refractive_index: 1.0,
+                },
+            }),
+            Box::new(Sphere {
+                center: Vector3::new(0.0, 3.0, -10.0),
+                radius: 5.0,
+                material: Material {
+                    colour: LightColour::y(),
+                    specular: Some(10.0),
+                    reflective: 0.0,
+                    transparency: 0.0,
+                    refractive_index: 1.0,
+                },
+            }),
+            Box::new(Sphere {
+                center: Vector3::new(-10.0, 3.0, 0.0),
+                radius: 5.0,
+                material: Material {
+                    colour: LightColour::new(1.0, 1.0, 0.0),
+                    specular: Some(1000.0),
+                    reflective: 0.5,
+                    transparency: 0.0,
+                    refractive_index: 1.0,
+                },
+            }),
+            // a glass ball in front of the camera
+            Box::new(Sphere {
+                center: Vector3::new(4.0, 1.0, 6.0),
+                radius: 2.0,
+                material: Material {
+                    colour: LightColour::from_element(1.0),
+                    specular: Some(500.0),
+                    reflective: 0.0,
+                    transparency: 0.9,
+                    refractive_index: 1.5,
+                },
+            }),
+            Box::new(Plane {
+                point: Vector3::new(0.0, -4.0, 0.0),
+                normal: Vector3::y(),
+                material: Material {
+                    colour: LightColour::from_element(0.6),
+                    specular: None,
+                    reflective: 0.2,
+                    transparency: 0.0,
+                    refractive_index: 1.0,
+                },
+            }),
+        ],
+        vec![
+            light,
+            Box::new(DirectionalLight {
+                direction: WorldVector::new(-1.0, -1.0, 1.0).normalize(),
+                colour: LightColour::from_element(1.0),
+            }),
+        ],
+        camera,
+        LightColour::from_element(0.3),
+    )
+}
+
+/// Adds the model `--model <path.obj>` to `world`, if one is given,
+/// scaled to 4 units across and centered left of the glass ball
+pub fn add_model(world: &mut World) -> Result<(), io::Error> {
+    let Some(path) = argument("--model") else {
+        return Ok(());
+    };
+    let mut meshes =
+        load_obj(Path::new(&path)).map_err(|e| io::Error::new(e.kind(), format!("{path}: {e}")))?;
+
+    let (min, max) = meshes
+        .iter()
+        .flat_map(Mesh::triangles)
+        .flat_map(|triangle| triangle.vertices)
+        .fold(
+            (
+                WorldVector::repeat(f64::INFINITY),
+                WorldVector::repeat(f64::NEG_INFINITY),
+            ),
+            |(min, max), vertex| (min.inf(&vertex), max.sup(&vertex)),
+        );
+    let extent = (max - min).max();
+    let scale = if extent > 0.0 { 4.0 / extent } else { 1.0 };
+    let offset = WorldVector::new(-4.0, -2.0, 6.0) - (min + max) / 2.0 * scale;
+    for mut mesh in meshes.drain(..) {
+        mesh.place(scale, offset);
+        world.add_shape(Box::new(mesh));
+    }
+    Ok(())
+}
+
+pub struct App<'a, M: CellMode = Blocks> {
+    /// Writes frames to Stdout on the present thread
+    swap_chain: SwapChain,
+    canvas: TerminalCanvas<M>,
     state: State,
     world: World,
+    /// Draws frames of the world to the canvas
+    renderer: Box<dyn Renderer>,
     /// "Window" title
     title: &'a str,
 
@@ -138,9 +304,28 @@ pub struct App<'a> {
 
     light_at: LightingContribution,
     fps_limit: Option<f64>,
+
+    /// Format screenshots are saved in
+    screenshot_format: ImageFormat,
+    /// Whether to save a screenshot after the next render
+    screenshot_requested: bool,
+    /// Whether the screenshot key was held last frame
+    screenshot_key_held: bool,
+    /// Result of the last screenshot, shown on the HUD
+    last_screenshot: Option<String>,
+
+    /// Records displayed frames to an asciicast file, `--record <path>`
+    recorder: Option<Recorder<BufWriter<File>>>,
+
+    /// Whether frames are wrapped in synchronized updates,
+    /// queried from the terminal when initialising
+    synchronized: bool,
 }
 
-impl<'a> App<'a> {
+impl<'a, M: CellMode> App<'a, M> {
+    /// Text cells the HUD needs, it isn't drawn on smaller terminals
+    const HUD_SIZE: Vector2<usize> = Vector2::new(24, 12);
+
     // ---------------- STATE HANDLING ----------------
 
     /// Processes the current state (update logic for states)
@@ -149,34 +334,22 @@ impl<'a> App<'a> {
             State::Running { start } => {
                 self.world.camera.update(self.frame_time.total);
                 let mouse_cell = self.calibration.mouse_cell();
-                self.light_at = match self.world.trace_ray(
-                    self.world
-                        .camera
-                        .from_canvas(mouse_cell, self.canvas.size()),
-                    1f64,
-                    10000f64,
-                ) {
+                let mouse_ray = self
+                    .world
+                    .camera
+                    .primary_ray(mouse_cell.cast() + Vector2::repeat(0.5), self.canvas.size());
+                self.light_at = match self.world.cast_ray(&mouse_ray, 1f64, 10000f64) {
                     Some(hit) => self.world.get_lighting(&hit),
                     None => LightingContribution::default(),
                 };
-                for y in 0..self.canvas.size().y {
-                    for x in 0..self.canvas.size().x {
-                        let canvas_position = Vector2::new(x, y);
-                        let colour = match self.world.trace_ray(
-                            self.world
-                                .camera
-                                .from_canvas(canvas_position, self.canvas.size()),
-                            1f64,
-                            10000f64,
-                        ) {
-                            Some(hit) => self.world.compute_lighting(&hit),
-                            None => Colour::zeros(),
-                        };
-                        self.canvas.put_pixel(colour, canvas_position);
-                    }
-                }
+                self.renderer.render(&self.world, &mut self.canvas);
                 self.canvas
                     .put_pixel(Colour::new(250, 160, 180), self.canvas.size() / 2);
+                // only the scene is drawn when the HUD doesn't fit
+                let text_size = self.canvas.text_size();
+                if text_size.x < Self::HUD_SIZE.x || text_size.y < Self::HUD_SIZE.y {
+                    return Ok(None);
+                }
                 let fmt = |label: &str, max_integrals: usize, max_decimals: usize, val: f64| {
                     format!(
                         " {0:}: {1:>2$}ms ",
@@ -335,31 +508,52 @@ impl<'a> App<'a> {
                 self.canvas.write(
                     format!(" T({:0>2}:{:0>2}) ", elapsed / 60, elapsed % 60),
                     Colour::from_element(40),
-                    Vector2::new((self.canvas.size().x - 10) / 2, 0),
+                    Vector2::new((self.canvas.text_size().x - 10) / 2, 0),
                 );
 
+                if let Some(screenshot) = &self.last_screenshot {
+                    self.canvas.write(
+                        format!(" {} ", screenshot),
+                        Colour::new(150, 150, 50),
+                        Vector2::new(0, self.canvas.text_size().y - 5),
+                    );
+                }
+
+                let statistics = *self.canvas.statistics();
+                self.canvas.write(
+                    format!(
+                        " OUTPUT: {:>8.1}KiB ({:>6} changed) {:?}{} ",
+                        statistics.bytes as f64 / 1024.0,
+                        statistics.changed,
+                        self.canvas.colour_depth(),
+                        if self.synchronized { " SYNC" } else { "" }
+                    ),
+                    Colour::new(150, 150, 50),
+                    Vector2::new(0, self.canvas.text_size().y - 4),
+                );
                 self.canvas.write(
-                    fmt("UPDATE", 3, 4, self.frame_time.update),
+                    format!(
+                        "{}({}) ",
+                        fmt("UPDATE", 3, 4, self.frame_time.update),
+                        self.renderer.name()
+                    ),
                     Colour::new(150, 50, 50),
-                    Vector2::new(0, self.canvas.size().y - 3),
+                    Vector2::new(0, self.canvas.text_size().y - 3),
                 );
                 self.canvas.write(
                     fmt("RENDER", 3, 4, self.frame_time.render),
                     Colour::new(50, 150, 50),
-                    Vector2::new(0, self.canvas.size().y - 2),
+                    Vector2::new(0, self.canvas.text_size().y - 2),
                 );
                 self.canvas.write(
                     fmt(" FRAME", 3, 4, self.frame_time.total),
                     Colour::new(50, 50, 150),
-                    Vector2::new(0, self.canvas.size().y - 1),
+                    Vector2::new(0, self.canvas.text_size().y - 1),
                 );
 
-                self.canvas.swap();
-
                 Ok(None)
             }
             State::Calibrating { second_stage } => {
-
                 self.canvas.put_pixel(
                     Colour::new(0, 255, 0),
                     if second_stage {
@@ -369,7 +563,6 @@ impl<'a> App<'a> {
                     },
                 );
 
-                self.canvas.full_swap();
                 self.canvas.write(
                     if second_stage {
                         "Click on the bottom-left of the highlighted square"
@@ -378,7 +571,10 @@ impl<'a> App<'a> {
                     }
                     .to_string(),
                     Colour::new(100, 0, 0),
-                    Vector2::new(self.canvas.size().x / 2 - 47 / 4, self.canvas.size().y / 2),
+                    Vector2::new(
+                        (self.canvas.text_size().x / 2).saturating_sub(47 / 4),
+                        self.canvas.text_size().y / 2,
+                    ),
                 );
                 Ok(None)
             }
@@ -413,6 +609,16 @@ impl<'a> App<'a> {
                 }
             }
             State::Running { start: _ } => {
+                // P saves a screenshot, once per press
+                let screenshot_key = inputbot::KeybdKey::PKey.is_pressed();
+                if screenshot_key && !self.screenshot_key_held {
+                    self.screenshot_requested = true;
+                }
+                self.screenshot_key_held = screenshot_key;
+
+                // hold F to display every frame in full, to compare output sizes
+                self.canvas
+                    .set_differential(!inputbot::KeybdKey::FKey.is_pressed());
                 self.world.camera.input();
                 let mouse_pos_this_frame = Calibration::mouse_position();
 
@@ -473,6 +679,37 @@ impl<'a> App<'a> {
         })
     }
 
+    /// Resizes the canvas to a new terminal size and keeps
+    /// the camera's aspect ratio matched to it
+    pub fn resize(&mut self, terminal_size: Vector2<u16>) -> Result<(), io::Error> {
+        self.canvas.resize(terminal_size);
+        self.world.camera.resize(self.canvas.size());
+        // the old frame may extend beyond the new canvas
+        self.swap_chain.back().execute(Clear(ClearType::All))?;
+        if let Some(recorder) = &mut self.recorder {
+            recorder.resize(self.frame_time.start, terminal_size)?;
+        }
+        Ok(())
+    }
+
+    /// Saves the canvas as an image in the working directory,
+    /// returns the path it was saved to
+    pub fn screenshot(&self) -> Result<String, io::Error> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = format!(
+            "screenshot-{}.{}",
+            timestamp,
+            self.screenshot_format.extension()
+        );
+        let mut file = BufWriter::new(File::create(&path)?);
+        export(&self.canvas, self.screenshot_format, &mut file)?;
+        file.flush()?;
+        Ok(path)
+    }
+
     /// Current state
     #[inline]
     pub fn state(&self) -> &State {
@@ -480,66 +717,38 @@ impl<'a> App<'a> {
     }
 }
 
-impl<'a> Application<'a> for App<'a> {
-
+impl<'a, M: CellMode + Default> Application<'a> for App<'a, M> {
     type Error = io::Error;
 
     fn fresh(title: &'a str) -> Self {
-        let canvas = BufferedCanvas::new();
-        let size = canvas.size();
+        let mut canvas = TerminalCanvas::new();
+        // `--colours truecolour|256|16`, otherwise detected from the environment
+        canvas.set_colour_depth(
+            argument("--colours")
+                .and_then(|name| ColourDepth::from_name(&name))
+                .unwrap_or_else(ColourDepth::detect),
+        );
+        // `--dither none|bayer|floyd-steinberg`
+        canvas.set_dither(
+            argument("--dither")
+                .and_then(|name| Dither::from_name(&name))
+                .unwrap_or_default(),
+        );
+        let world = demo_world(&canvas);
         let this = Self {
-            _buf_writer: StdoutWriter::new(io::stdout().lock()),
+            // `--buffers 1|2|3`, frames rendered ahead of the one being written
+            swap_chain: SwapChain::new(
+                BufWriter::new(io::stdout()),
+                argument("--buffers")
+                    .and_then(|buffers| buffers.parse().ok())
+                    .filter(|&buffers| buffers > 0)
+                    .unwrap_or(2),
+            ),
             canvas,
             state: State::Initialising,
-            world: World {
-                ambient: LightColour::from_element(0.3),
-                spheres: vec![
-                    Sphere {
-                        center: Vector3::new(0.0, 6.0, 10.0),
-                        radius: 5.0,
-                        material: Material {
-                            colour: LightColour::x(),
-                            specular: Some(500.0),
-                        },
-                    },
-                    Sphere {
-                        center: Vector3::new(10.0, 6.0, 0.0),
-                        radius: 5.0,
-                        material: Material {
-                            colour: LightColour::z(),
-                            specular: Some(500.0),
-                        },
-                    },
-                    Sphere {
-                        center: Vector3::new(0.0, 3.0, -10.0),
-                        radius: 5.0,
-                        material: Material {
-                            colour: LightColour::y(),
-                            specular: Some(10.0),
-                        },
-                    },
-                    Sphere {
-                        center: Vector3::new(-10.0, 3.0, 0.0),
-                        radius: 5.0,
-                        material: Material {
-                            colour: LightColour::new(1.0, 1.0, 0.0),
-                            specular: Some(1000.0),
-                        },
-                    },
-                ],
-                light_sources: vec![
-                    Box::new(PointLight {
-                        position: Vector3::zeros(),
-                        colour: LightColour::from_element(0.5),
-                    }),
-                    Box::new(DirectionalLight {
-                        direction: WorldVector::new(-1.0, -1.0, 1.0).normalize(),
-                        colour: LightColour::from_element(1.0),
-                    }),
-                ],
-                camera: Camera::new(size),
-            },
-            title, 
+            renderer: renderer(),
+            world,
+            title,
             calibration: Calibration {
                 top_left: Vector2::zero(),
                 bottom_right: Vector2::zero(),
@@ -554,22 +763,56 @@ impl<'a> Application<'a> for App<'a> {
             light_at: LightingContribution::default(),
             mouse_pos_last_frame: Vector2::zero(),
             fps_limit: Some(144f64),
+            // `--screenshot-format png|ppm`
+            screenshot_format: argument("--screenshot-format")
+                .and_then(|name| ImageFormat::from_name(&name))
+                .unwrap_or(ImageFormat::Png),
+            screenshot_requested: false,
+            screenshot_key_held: false,
+            last_screenshot: None,
+            recorder: None,
+            synchronized: false,
         };
 
         this
     }
 
     fn initialise(&mut self) -> Result<(), Self::Error> {
-        self._buf_writer.execute(EnterAlternateScreen)?;
-        self._buf_writer.execute(cursor::Hide)?;
-        self._buf_writer.execute(SetTitle(self.title))?;
+        add_model(&mut self.world)?;
+        self.swap_chain.back().execute(EnterAlternateScreen)?;
+        self.swap_chain.back().execute(cursor::Hide)?;
+        self.swap_chain.back().execute(SetTitle(self.title))?;
         enable_raw_mode()?;
+        // the query follows the setup through the swap chain, so it's answered
+        // after the terminal has switched screens, terminals that don't
+        // support synchronized updates still answer within a few milliseconds
+        if io::stdin().is_terminal() && io::stdout().is_terminal() {
+            self.swap_chain
+                .back()
+                .extend_from_slice(synchronized::QUERY);
+            self.swap_chain.swap()?;
+            self.synchronized = synchronized::read_answer(Duration::from_millis(200));
+        }
+        if let Some(path) = argument("--record") {
+            let (columns, rows) = terminal::size()?;
+            self.recorder = Some(Recorder::new(
+                BufWriter::new(File::create(path)?),
+                self.frame_time.start,
+                Vector2::new(columns, rows),
+                self.title,
+            )?);
+        }
         self.transit(Event::Initialised)?;
 
         Ok(())
     }
 
     fn input(&mut self) -> Result<(), Self::Error> {
+        while terminal_event::poll(Duration::ZERO)? {
+            if let TerminalEvent::Resize(columns, rows) = terminal_event::read()? {
+                self.resize(Vector2::new(columns, rows))?;
+            }
+        }
         if let Some(event) = self.state_input() {
             self.transit(event)?;
         }
@@ -590,10 +833,26 @@ impl<'a> Application<'a> for App<'a> {
 
     fn render(&mut self) -> Result<(), Self::Error> {
         let now = Instant::now();
-        let res = self.canvas.display(&mut self._buf_writer);
+        self.canvas.display(self.swap_chain.back())?;
+        if self.synchronized {
+            self.swap_chain.back().extend_from_slice(synchronized::END);
+        }
         self.frame_time.render = now.elapsed().as_millis_f64();
-        res
 
+        if let Some(recorder) = &mut self.recorder {
+            recorder.output(self.frame_time.start, self.swap_chain.back())?;
+        }
+        // written on the present thread while the next frame renders
+        self.swap_chain.swap()?;
+
+        if self.screenshot_requested {
+            self.screenshot_requested = false;
+            self.last_screenshot = Some(match self.screenshot() {
+                Ok(path) => format!("SAVED: {}", path),
+                Err(err) => format!("SCREENSHOT FAILED: {}", err),
+            });
+        }
+        Ok(())
     }
     fn begin_frame(&mut self) -> Result<(), Self::Error> {
         self.frame_time.start = Instant::now();
@@ -601,36 +860,42 @@ impl<'a> Application<'a> for App<'a> {
     }
     fn end_frame(&mut self) -> Result<(), Self::Error> {
         self.frame_time.total = self.frame_time.start.elapsed().as_millis_f64();
-        
 
         // Wait if we need to
         if let Some(fps) = self.fps_limit {
-            let diff = 1000.0/fps - self.frame_time.total;
+            let diff = 1000.0 / fps - self.frame_time.total;
             if diff > 0.0 {
-                std::thread::sleep(Duration::from_micros((diff*1000.0) as u64));
+                std::thread::sleep(Duration::from_micros((diff * 1000.0) as u64));
             }
         }
 
         Ok(())
-
     }
 
     fn clear(&mut self) -> Result<(), Self::Error> {
-        self._buf_writer.execute(cursor::MoveTo(0, 0))?;
+        // the whole frame is drawn at once, including any clear on resize
+        if self.synchronized {
+            self.swap_chain
+                .back()
+                .extend_from_slice(synchronized::BEGIN);
+        }
+        self.swap_chain.back().execute(cursor::MoveTo(0, 0))?;
         self.canvas.clear();
         Ok(())
     }
-    
+
     fn end(&mut self) -> Result<(), Self::Error> {
-        self._buf_writer.execute(Clear(ClearType::All))?;
-        self._buf_writer.flush()?;
-        self._buf_writer.execute(LeaveAlternateScreen)?;
+        self.swap_chain.back().execute(Clear(ClearType::All))?;
+        self.swap_chain.back().execute(LeaveAlternateScreen)?;
+        self.swap_chain.finish()?;
         disable_raw_mode()?;
+        if let Some(recorder) = &mut self.recorder {
+            recorder.flush()?;
+        }
         Ok(())
     }
 
     fn is_running(&self) -> bool {
         self.state != State::Exiting
     }
-
 }
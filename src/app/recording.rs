@@ -0,0 +1,92 @@
+use std::{
+    io::{self, Write},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use nalgebra::Vector2;
+
+/// Records what is written to the terminal as an asciicast v2
+/// file, the recording can be replayed with `asciinema play`
+pub struct Recorder<W: Write> {
+    out: W,
+    /// When the recording started, event times are relative to it
+    start: Instant,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Starts a recording of a terminal of `terminal_size` columns by rows,
+    /// writes the header to `out`
+    pub fn new(
+        mut out: W,
+        start: Instant,
+        terminal_size: Vector2<u16>,
+        title: &str,
+    ) -> Result<Self, io::Error> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        writeln!(
+            out,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}, \"title\": {}}}",
+            terminal_size.x,
+            terminal_size.y,
+            timestamp,
+            json_string(title.as_bytes())
+        )?;
+        Ok(Self { out, start })
+    }
+
+    /// Records `data` written to the terminal at `at`
+    pub fn output(&mut self, at: Instant, data: &[u8]) -> Result<(), io::Error> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        writeln!(
+            self.out,
+            "[{:.6}, \"o\", {}]",
+            self.time(at),
+            json_string(data)
+        )
+    }
+
+    /// Records the terminal being resized to `terminal_size` at `at`
+    pub fn resize(&mut self, at: Instant, terminal_size: Vector2<u16>) -> Result<(), io::Error> {
+        writeln!(
+            self.out,
+            "[{:.6}, \"r\", \"{}x{}\"]",
+            self.time(at),
+            terminal_size.x,
+            terminal_size.y
+        )
+    }
+
+    pub fn flush(&mut self) -> Result<(), io::Error> {
+        self.out.flush()
+    }
+
+    /// Seconds since the recording started
+    #[inline]
+    fn time(&self, at: Instant) -> f64 {
+        at.saturating_duration_since(self.start).as_secs_f64()
+    }
+}
+
+/// Quotes and escapes terminal output as a JSON string,
+/// invalid UTF-8 is replaced
+fn json_string(data: &[u8]) -> String {
+    let mut string = String::with_capacity(data.len() + 2);
+    string.push('"');
+    for char in String::from_utf8_lossy(data).chars() {
+        match char {
+            '"' => string.push_str("\\\""),
+            '\\' => string.push_str("\\\\"),
+            '\n' => string.push_str("\\n"),
+            '\r' => string.push_str("\\r"),
+            c if (c as u32) < 0x20 => string.push_str(&format!("\\u{:04x}", c as u32)),
+            c => string.push(c),
+        }
+    }
+    string.push('"');
+    string
+}
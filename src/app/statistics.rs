@@ -0,0 +1,27 @@
+/// Summary of a series of timings
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub min: f64,
+    pub mean: f64,
+    /// 95th percentile, by nearest rank
+    pub p95: f64,
+    pub max: f64,
+}
+
+impl Summary {
+    /// Summarises `samples`, all zero when there are none
+    pub fn of(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let rank = (0.95 * sorted.len() as f64).ceil() as usize;
+        Self {
+            min: sorted[0],
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            p95: sorted[rank.max(1) - 1],
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
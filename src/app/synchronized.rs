@@ -0,0 +1,141 @@
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+/// Starts a synchronized update, the terminal holds off drawing
+/// until `END` so a frame is never shown half written
+pub const BEGIN: &[u8] = b"\x1B[?2026h";
+/// Ends a synchronized update, the terminal draws the frame
+pub const END: &[u8] = b"\x1B[?2026l";
+/// Asks the terminal whether it supports synchronized updates, DEC mode
+/// 2026, then for the primary device attributes, which every terminal
+/// answers, so we know when to stop waiting
+pub const QUERY: &[u8] = b"\x1B[?2026$p\x1B[c";
+
+/// Reads the terminal's answer to `QUERY` from stdin, returning as soon as
+/// it is complete or after `timeout`. Must be called in raw mode, before
+/// anything else reads stdin. Terminals that don't answer are assumed not
+/// to support synchronized updates.
+pub fn read_answer(timeout: Duration) -> bool {
+    #[cfg(unix)]
+    let next = |timeout| {
+        use std::os::fd::AsRawFd;
+        read_byte(io::stdin().as_raw_fd(), timeout)
+    };
+    #[cfg(windows)]
+    let next = read_console_byte;
+
+    read_report(next, timeout).unwrap_or(false)
+}
+
+/// Reads the answer to `QUERY` a byte at a time from `next`, which waits
+/// up to the time left for a byte. Stops at the end of the answer, so any
+/// input after it is left to be read, or returns false after `timeout`.
+pub fn read_report(
+    mut next: impl FnMut(Duration) -> io::Result<Option<u8>>,
+    timeout: Duration,
+) -> io::Result<bool> {
+    let deadline = Instant::now() + timeout;
+    let mut response = Vec::new();
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Ok(false);
+        }
+        if let Some(byte) = next(left)? {
+            response.push(byte);
+            if let Some(supported) = parse_report(&response) {
+                return Ok(supported);
+            }
+        }
+    }
+}
+
+/// Waits up to `timeout` for a byte from `fd` and reads it. Reads the file
+/// descriptor directly, stdin's buffer would take input past the answer.
+#[cfg(unix)]
+pub fn read_byte(fd: std::os::fd::RawFd, timeout: Duration) -> io::Result<Option<u8>> {
+    let mut poll = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let millis = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+    match unsafe { libc::poll(&mut poll, 1, millis) } {
+        0 => return Ok(None),
+        -1 => {
+            let err = io::Error::last_os_error();
+            return match err.kind() {
+                io::ErrorKind::Interrupted => Ok(None),
+                _ => Err(err),
+            };
+        }
+        _ => {}
+    }
+
+    let mut byte = 0u8;
+    match unsafe { libc::read(fd, &mut byte as *mut u8 as *mut libc::c_void, 1) } {
+        1 => Ok(Some(byte)),
+        0 => Err(io::ErrorKind::UnexpectedEof.into()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Waits up to `timeout` for console input and reads one record, the
+/// terminal's answer arrives as key presses of its characters. Other
+/// records are dropped, they're only read while the query is answered.
+#[cfg(windows)]
+fn read_console_byte(timeout: Duration) -> io::Result<Option<u8>> {
+    use winapi::um::{
+        consoleapi::ReadConsoleInputW,
+        processenv::GetStdHandle,
+        synchapi::WaitForSingleObject,
+        winbase::{INFINITE, STD_INPUT_HANDLE, WAIT_OBJECT_0},
+        wincontypes::{INPUT_RECORD, KEY_EVENT},
+    };
+
+    let handle = unsafe { GetStdHandle(STD_INPUT_HANDLE) };
+    let millis = timeout.as_millis().min(INFINITE as u128 - 1) as u32;
+    if unsafe { WaitForSingleObject(handle, millis) } != WAIT_OBJECT_0 {
+        return Ok(None);
+    }
+
+    let mut record: INPUT_RECORD = unsafe { std::mem::zeroed() };
+    let mut read = 0;
+    if unsafe { ReadConsoleInputW(handle, &mut record, 1, &mut read) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if read == 1 && record.EventType == KEY_EVENT {
+        let key = unsafe { record.Event.KeyEvent() };
+        let char = unsafe { *key.uChar.UnicodeChar() };
+        if key.bKeyDown != 0 && char != 0 && char < 0x80 {
+            return Ok(Some(char as u8));
+        }
+    }
+    Ok(None)
+}
+
+/// Parses the terminal's answer to `QUERY`, returns `None` until the
+/// device attributes have been read. The mode is supported if it was
+/// reported as set or reset, `ESC[?2026;1$y` or `ESC[?2026;2$y`.
+pub fn parse_report(response: &[u8]) -> Option<bool> {
+    let mut supported = false;
+    let mut rest = response;
+    while let Some(start) = rest.windows(3).position(|w| w == b"\x1B[?") {
+        let sequence = &rest[start + 3..];
+        // parameters then the final byte
+        let end = sequence.iter().position(|b| b.is_ascii_alphabetic())?;
+        match sequence[end] {
+            b'y' => {
+                if let Some(state) = sequence[..end].strip_prefix(b"2026;") {
+                    supported = state == b"1$" || state == b"2$";
+                }
+            }
+            b'c' => return Some(supported),
+            _ => {}
+        }
+        rest = &sequence[end + 1..];
+    }
+    None
+}
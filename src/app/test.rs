@@ -0,0 +1,134 @@
+#![cfg(test)]
+use super::recording::*;
+use super::statistics::Summary;
+use super::synchronized;
+use nalgebra::Vector2;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_recording() -> Result<(), std::io::Error> {
+    let start = Instant::now();
+    let mut cast = Vec::new();
+    let mut recorder = Recorder::new(&mut cast, start, Vector2::new(80, 24), "a \"demo\"")?;
+    recorder.output(
+        start + Duration::from_millis(1500),
+        "\x1b[0m▀\\\n".as_bytes(),
+    )?;
+    recorder.output(start + Duration::from_secs(2), &[])?;
+    recorder.resize(start + Duration::from_secs(3), Vector2::new(100, 30))?;
+
+    let cast = String::from_utf8(cast).unwrap();
+    let lines = cast.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("{\"version\": 2, \"width\": 80, \"height\": 24, "));
+    assert!(lines[0].ends_with("\"title\": \"a \\\"demo\\\"\"}"));
+    assert_eq!(lines[1], "[1.500000, \"o\", \"\\u001b[0m▀\\\\\\n\"]");
+    assert_eq!(lines[2], "[3.000000, \"r\", \"100x30\"]");
+    Ok(())
+}
+
+#[test]
+fn test_synchronized_report() {
+    // set, reset, not recognised and permanently reset
+    assert_eq!(
+        synchronized::parse_report(b"\x1B[?2026;2$y\x1B[?62;22c"),
+        Some(true)
+    );
+    assert_eq!(
+        synchronized::parse_report(b"\x1B[?2026;1$y\x1B[?1;2c"),
+        Some(true)
+    );
+    assert_eq!(
+        synchronized::parse_report(b"\x1B[?2026;0$y\x1B[?1;2c"),
+        Some(false)
+    );
+    assert_eq!(
+        synchronized::parse_report(b"\x1B[?2026;4$y\x1B[?1;2c"),
+        Some(false)
+    );
+    // terminals that ignore the request only answer the device attributes
+    assert_eq!(synchronized::parse_report(b"\x1B[?6c"), Some(false));
+    // incomplete answers wait for more
+    assert_eq!(synchronized::parse_report(b"\x1B[?2026;2$y"), None);
+    assert_eq!(
+        synchronized::parse_report(b"\x1B[?2026;2$y\x1B[?62;2"),
+        None
+    );
+}
+
+#[test]
+fn test_synchronized_answer() -> Result<(), std::io::Error> {
+    // the answer arrives in pieces with waits between them,
+    // input after it is left for the app
+    let answer = b"\x1B[?2026;2$y\x1B[?62;22c";
+    let mut input = answer[..5].iter().map(|&b| Some(b)).collect::<Vec<_>>();
+    input.push(None);
+    input.extend(answer[5..].iter().map(|&b| Some(b)));
+    input.insert(14, None);
+    input.push(Some(b'q'));
+    let mut input = input.into_iter();
+
+    let supported =
+        synchronized::read_report(|_| Ok(input.next().flatten()), Duration::from_secs(5))?;
+    assert!(supported);
+    assert_eq!(input.next(), Some(Some(b'q')));
+
+    // terminals that never answer time out
+    let start = Instant::now();
+    let supported = synchronized::read_report(
+        |left| {
+            std::thread::sleep(left);
+            Ok(None)
+        },
+        Duration::from_millis(20),
+    )?;
+    assert!(!supported);
+    assert!(start.elapsed() < Duration::from_secs(1));
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_synchronized_answer_pipe() -> Result<(), std::io::Error> {
+    use std::{fs::File, io::Write, os::fd::FromRawFd};
+
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let (read, mut write) = (fds[0], unsafe { File::from_raw_fd(fds[1]) });
+    let reader = unsafe { File::from_raw_fd(read) };
+
+    write.write_all(b"\x1B[?2026;1$y\x1B[?1;2cq")?;
+    let supported = synchronized::read_report(
+        |left| synchronized::read_byte(read, left),
+        Duration::from_secs(5),
+    )?;
+    assert!(supported);
+    // only the answer was read
+    assert_eq!(synchronized::read_byte(read, Duration::ZERO)?, Some(b'q'));
+    assert_eq!(synchronized::read_byte(read, Duration::ZERO)?, None);
+
+    // a terminal ignoring both requests
+    let start = Instant::now();
+    let supported = synchronized::read_report(
+        |left| synchronized::read_byte(read, left),
+        Duration::from_millis(20),
+    )?;
+    assert!(!supported);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    drop(reader);
+    Ok(())
+}
+
+#[test]
+fn test_summary() {
+    let samples = (1..=20).rev().map(f64::from).collect::<Vec<_>>();
+    let summary = Summary::of(&samples);
+    assert_eq!(summary.min, 1.0);
+    assert_eq!(summary.mean, 10.5);
+    // the 19th of 20 sorted samples
+    assert_eq!(summary.p95, 19.0);
+    assert_eq!(summary.max, 20.0);
+
+    assert_eq!(Summary::of(&[4.0]).p95, 4.0);
+    assert_eq!(Summary::of(&[]), Summary::default());
+}
@@ -0,0 +1,330 @@
+use super::{Ray, WorldVector};
+
+/// An axis-aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: WorldVector,
+    pub max: WorldVector,
+}
+
+impl Aabb {
+    /// A box containing nothing, growing it by anything gives that thing's box
+    pub fn empty() -> Self {
+        Self {
+            min: WorldVector::repeat(f64::INFINITY),
+            max: WorldVector::repeat(f64::NEG_INFINITY),
+        }
+    }
+
+    /// The smallest box containing all of `points`
+    pub fn around(points: impl IntoIterator<Item = WorldVector>) -> Self {
+        points
+            .into_iter()
+            .fold(Self::empty(), |bounds, point| Self {
+                min: bounds.min.inf(&point),
+                max: bounds.max.sup(&point),
+            })
+    }
+
+    /// The smallest box containing both boxes
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.inf(&other.min),
+            max: self.max.sup(&other.max),
+        }
+    }
+
+    pub fn center(&self) -> WorldVector {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Surface area, the chance a ray through a parent box
+    /// hits this one is proportional to it
+    pub fn surface_area(&self) -> f64 {
+        let size = (self.max - self.min).sup(&WorldVector::zeros());
+        2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+    }
+
+    /// Returns where a ray from `origin`, with the reciprocal of its
+    /// direction `inverse`, enters the box between `t_min` and `t_max`
+    #[inline]
+    fn entry(
+        &self,
+        origin: WorldVector,
+        inverse: WorldVector,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<f64> {
+        let (mut near, mut far) = (t_min, t_max);
+        for axis in 0..3 {
+            let t0 = (self.min[axis] - origin[axis]) * inverse[axis];
+            let t1 = (self.max[axis] - origin[axis]) * inverse[axis];
+            near = near.max(t0.min(t1));
+            far = far.min(t0.max(t1));
+        }
+        (near <= far).then_some(near)
+    }
+}
+
+/// A node of the hierarchy, a leaf holds `count` items from `start`,
+/// otherwise its children are at `start` and `start + 1`
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    bounds: Aabb,
+    start: usize,
+    count: usize,
+}
+
+/// A bounding volume hierarchy, finds which items a ray might hit without
+/// testing them all. Items are referred to by their index, they're split
+/// by the surface area heuristic when built and can be refit after moving.
+/// Unbounded items, such as planes, are tested against every ray.
+#[derive(Debug, Default, Clone)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    /// Indices of bounded items, in leaf order
+    items: Vec<usize>,
+    /// Indices of items without bounds
+    unbounded: Vec<usize>,
+}
+
+impl Bvh {
+    /// Bins candidate splits are sorted into along each axis
+    const BINS: usize = 12;
+    /// Cost of visiting a node relative to testing an item
+    const TRAVERSAL_COST: f64 = 1.0;
+
+    /// Builds a hierarchy over items with `bounds`, `None` if unbounded
+    pub fn build(bounds: &[Option<Aabb>]) -> Self {
+        let (items, unbounded): (Vec<_>, Vec<_>) =
+            (0..bounds.len()).partition(|&i| bounds[i].is_some());
+        let mut this = Self {
+            nodes: Vec::new(),
+            items,
+            unbounded,
+        };
+        if !this.items.is_empty() {
+            let bounds = bounds
+                .iter()
+                .map(|b| b.unwrap_or(Aabb::empty()))
+                .collect::<Vec<_>>();
+            this.nodes.push(Node {
+                bounds: Aabb::empty(),
+                start: 0,
+                count: this.items.len(),
+            });
+            this.split(0, &bounds);
+        }
+        this
+    }
+
+    /// Updates the boxes of the hierarchy after items moved, keeping
+    /// its shape. It gets slower the further items move from where
+    /// they were built, rebuild it then.
+    pub fn refit(&mut self, bounds: &[Option<Aabb>]) {
+        // children always come after their parent
+        for i in (0..self.nodes.len()).rev() {
+            let Node { start, count, .. } = self.nodes[i];
+            self.nodes[i].bounds = if count > 0 {
+                self.items[start..start + count]
+                    .iter()
+                    .fold(Aabb::empty(), |b, &item| {
+                        b.union(&bounds[item].unwrap_or(Aabb::empty()))
+                    })
+            } else {
+                self.nodes[start]
+                    .bounds
+                    .union(&self.nodes[start + 1].bounds)
+            };
+        }
+    }
+
+    /// Splits the items of leaf `node` in two while that's
+    /// estimated to make tracing faster
+    fn split(&mut self, node: usize, bounds: &[Aabb]) {
+        let Node { start, count, .. } = self.nodes[node];
+        let items = &mut self.items[start..start + count];
+        let node_bounds = items
+            .iter()
+            .fold(Aabb::empty(), |b, &item| b.union(&bounds[item]));
+        self.nodes[node].bounds = node_bounds;
+        if count < 2 {
+            return;
+        }
+
+        // splits are only considered between bins of item centers
+        let centers = Aabb::around(items.iter().map(|&item| bounds[item].center()));
+        let bin = |axis: usize, item: usize| {
+            let extent = centers.max[axis] - centers.min[axis];
+            let offset = (bounds[item].center()[axis] - centers.min[axis]) / extent;
+            ((offset * Self::BINS as f64) as usize).min(Self::BINS - 1)
+        };
+        let mut best: Option<(f64, usize, usize)> = None;
+        for axis in 0..3 {
+            if centers.max[axis] - centers.min[axis] <= 0.0 {
+                continue;
+            }
+            let mut bins = [(0, Aabb::empty()); Self::BINS];
+            for &item in items.iter() {
+                let (count, b) = &mut bins[bin(axis, item)];
+                *count += 1;
+                *b = b.union(&bounds[item]);
+            }
+            // items and area right of each split, swept from the right
+            let mut right = [(0, 0.0); Self::BINS];
+            let (mut right_count, mut right_bounds) = (0, Aabb::empty());
+            for split in (1..Self::BINS).rev() {
+                right_count += bins[split].0;
+                right_bounds = right_bounds.union(&bins[split].1);
+                right[split] = (right_count, right_bounds.surface_area());
+            }
+            let (mut left_count, mut left_bounds) = (0, Aabb::empty());
+            for split in 1..Self::BINS {
+                left_count += bins[split - 1].0;
+                left_bounds = left_bounds.union(&bins[split - 1].1);
+                let (right_count, right_area) = right[split];
+                if left_count == 0 || right_count == 0 {
+                    continue;
+                }
+                let cost = left_count as f64 * left_bounds.surface_area()
+                    + right_count as f64 * right_area;
+                if best.is_none_or(|(best, ..)| cost < best) {
+                    best = Some((cost, axis, split));
+                }
+            }
+        }
+
+        // a leaf costs testing all its items
+        let Some((cost, axis, split)) = best else {
+            return;
+        };
+        let area = node_bounds.surface_area();
+        if area > 0.0 && Self::TRAVERSAL_COST + cost / area >= count as f64 {
+            return;
+        }
+
+        let mut left = 0;
+        for i in 0..count {
+            if bin(axis, items[i]) < split {
+                items.swap(i, left);
+                left += 1;
+            }
+        }
+        let children = self.nodes.len();
+        self.nodes.push(Node {
+            bounds: Aabb::empty(),
+            start,
+            count: left,
+        });
+        self.nodes.push(Node {
+            bounds: Aabb::empty(),
+            start: start + left,
+            count: count - left,
+        });
+        self.nodes[node] = Node {
+            bounds: node_bounds,
+            start: children,
+            count: 0,
+        };
+        self.split(children, bounds);
+        self.split(children + 1, bounds);
+    }
+
+    /// Returns the index of the item `ray` hits nearest, and what
+    /// `intersect` returned for it. `intersect` is given an item and
+    /// the furthest hit that matters, and returns how far along the
+    /// ray it's hit, if it is.
+    pub fn closest<T>(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        mut intersect: impl FnMut(usize, f64) -> Option<(f64, T)>,
+    ) -> Option<(usize, T)> {
+        let mut closest: Option<(usize, f64, T)> = None;
+        let mut consider = |item: usize, closest: &mut Option<(usize, f64, T)>| {
+            let t_max = closest.as_ref().map_or(t_max, |&(_, t, _)| t);
+            if let Some((t, hit)) = intersect(item, t_max) {
+                *closest = Some((item, t, hit));
+            }
+        };
+        for &item in &self.unbounded {
+            consider(item, &mut closest);
+        }
+
+        let (origin, inverse) = (ray.origin(), (-ray.direction()).map(|d| 1.0 / d));
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(node) = stack.pop() {
+            let Node {
+                bounds,
+                start,
+                count,
+            } = self.nodes[node];
+            let t_max = closest.as_ref().map_or(t_max, |&(_, t, _)| t);
+            if bounds.entry(origin, inverse, t_min, t_max).is_none() {
+                continue;
+            }
+            if count > 0 {
+                for &item in &self.items[start..start + count] {
+                    consider(item, &mut closest);
+                }
+                continue;
+            }
+            // the nearer child is visited first, it's more likely to
+            // have the closest hit and cut the further one short
+            let entry = |child: usize| {
+                self.nodes[child]
+                    .bounds
+                    .entry(origin, inverse, t_min, t_max)
+                    .unwrap_or(f64::INFINITY)
+            };
+            if entry(start) < entry(start + 1) {
+                stack.extend([start + 1, start]);
+            } else {
+                stack.extend([start, start + 1]);
+            }
+        }
+        closest.map(|(item, _, hit)| (item, hit))
+    }
+
+    /// Whether `hit` returns true for any item `ray` might hit between
+    /// `t_min` and `t_max`, stops at the first it does
+    pub fn any(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        mut hit: impl FnMut(usize) -> bool,
+    ) -> bool {
+        if self.unbounded.iter().any(|&item| hit(item)) {
+            return true;
+        }
+        let (origin, inverse) = (ray.origin(), (-ray.direction()).map(|d| 1.0 / d));
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(node) = stack.pop() {
+            let Node {
+                bounds,
+                start,
+                count,
+            } = self.nodes[node];
+            if bounds.entry(origin, inverse, t_min, t_max).is_none() {
+                continue;
+            }
+            if count == 0 {
+                stack.extend([start, start + 1]);
+            } else if self.items[start..start + count]
+                .iter()
+                .any(|&item| hit(item))
+            {
+                return true;
+            }
+        }
+        false
+    }
+}
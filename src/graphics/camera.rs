@@ -1,10 +1,14 @@
-use nalgebra::{Matrix4, Vector2, Vector3, Vector4};
+use nalgebra::{Matrix4, Vector2, Vector3};
 
-use super::{util::Direction, CanvasVector, WorldVector};
+use super::{util::Direction, CanvasVector, Ray, WorldVector};
 
 pub struct Camera {
+    /// Width of the view relative to its height
     aspect_ratio: f64,
+    /// Vertical field of view, in radians
     fov: f64,
+    /// Width of a canvas pixel relative to its height
+    pixel_aspect: f64,
     
     pub position: WorldVector,
 
@@ -39,6 +43,7 @@ impl Camera {
 
             aspect_ratio: canvas_size.x as f64/canvas_size.y as f64,
             fov: std::f64::consts::PI / 2.0, // radians, 90 degrees 
+            pixel_aspect: 1.0,
 
             position: Vector3::new(0.0, 0.0, 0.0),
             basis: Basis {
@@ -55,6 +60,16 @@ impl Camera {
             movement: Direction::empty(),
         }
     }
+    /// Matches the aspect ratio to a resized canvas
+    pub fn resize(&mut self, canvas_size: CanvasVector) {
+        self.aspect_ratio = self.pixel_aspect * canvas_size.x as f64 / canvas_size.y as f64;
+    }
+    /// Sets the width of the canvas' pixels relative to their height,
+    /// so the view isn't stretched on canvases of tall pixels
+    pub fn set_pixel_aspect(&mut self, pixel_aspect: f64, canvas_size: CanvasVector) {
+        self.pixel_aspect = pixel_aspect;
+        self.resize(canvas_size);
+    }
     pub fn get_perspective(&self) -> Matrix4<f64> {
         Matrix4::new_perspective(self.aspect_ratio, self.fov, self.z_near, self.z_far)
     }
@@ -66,8 +81,18 @@ impl Camera {
         )
     }
 
-    pub fn from_canvas(&self, canvas_position: CanvasVector, canvas_size: CanvasVector) -> WorldVector {
-        (self.get_view() * Vector4::new(-(canvas_position.x as f64)/canvas_size.x as f64, canvas_position.y as f64/canvas_size.y as f64, self.z_near, 1.0)).xyz()
+    /// Returns the ray from the camera through `pixel` on a canvas of
+    /// `canvas_size` pixels. `pixel` is in pixels from the top left corner
+    /// of the canvas, a pixel's center is half a pixel into it.
+    pub fn primary_ray(&self, pixel: Vector2<f64>, canvas_size: CanvasVector) -> Ray {
+        // -1 to 1 across the canvas, left to right and bottom to top
+        let x = 2.0 * pixel.x / canvas_size.x as f64 - 1.0;
+        let y = 1.0 - 2.0 * pixel.y / canvas_size.y as f64;
+        let half_height = (self.fov / 2.0).tan();
+        let direction = self.basis.forward
+            + self.basis.right * (x * half_height * self.aspect_ratio)
+            + self.basis.up * (y * half_height);
+        Ray::new(self.position, self.position + direction)
     }
     #[inline]
     pub fn model_view(&self) -> Matrix4<f64> {
@@ -109,9 +134,16 @@ impl Camera {
         self.position += movement * Self::MOVEMENT_SPEED * delta/1000.0;
     }
     pub fn process_mouse_motion(&mut self, mouse_delta: Vector2<i16>, delta: f64) {
-        self.yaw -= Self::MOUSE_SENSITITVITY * delta/1000.0 * mouse_delta.x as f64;
-        self.pitch += (Self::MOUSE_SENSITITVITY * delta/1000.0 * mouse_delta.y as f64)
+        let yaw = self.yaw - Self::MOUSE_SENSITITVITY * delta/1000.0 * mouse_delta.x as f64;
+        // stops short of straight up or down, past them the view flips over
+        let pitch = (self.pitch - Self::MOUSE_SENSITITVITY * delta/1000.0 * mouse_delta.y as f64)
             .clamp(-std::f64::consts::FRAC_PI_2+0.01, std::f64::consts::FRAC_PI_2-0.01);
+        self.look(yaw, pitch);
+    }
+    /// Points the camera by its yaw and pitch, in radians
+    pub fn look(&mut self, yaw: f64, pitch: f64) {
+        self.yaw = yaw;
+        self.pitch = pitch;
 
         let (cos_yaw, sin_yaw) = (self.yaw.cos(), -self.yaw.sin());
         let (cos_pitch, sin_pitch) = (self.pitch.cos(), self.pitch.sin());
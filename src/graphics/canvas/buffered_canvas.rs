@@ -2,9 +2,9 @@ use std::io::Write;
 
 use nalgebra::Vector2;
 
-use crate::{app::StdoutWriter, graphics::Colour};
+use crate::graphics::Colour;
 
-use super::Canvas;
+use super::{Canvas, ColourDepth};
 
 const PREFIX_SIZE: usize = 21;
 const EOL_SIZE: usize = 5;
@@ -15,6 +15,9 @@ where
     pub(super) _buffers: [[u8; WIDTH * HEIGHT * PREFIX_SIZE + EOL_SIZE * HEIGHT]; BUFFERS],
     pub(super) _display_index: usize,
     pub(super) _edit_index: usize,
+    /// Colours are quantised to its palette, the cells keep
+    /// their fixed size truecolour escape codes
+    depth: ColourDepth,
 }
 
 impl<'a, const WIDTH: usize, const HEIGHT: usize, const BUFFERS: usize>
@@ -40,6 +43,7 @@ where
             _buffers: [prepared_buffer; BUFFERS],
             _display_index: 0,
             _edit_index: BUFFERS - 1,
+            depth: ColourDepth::TrueColour,
         };
 
         // prepare at least one buffer and swap to it
@@ -91,6 +95,16 @@ where
     }
 }
 
+impl<'a, const WIDTH: usize, const HEIGHT: usize, const BUFFERS: usize> Default
+    for BufferedCanvas<'a, WIDTH, HEIGHT, BUFFERS>
+where
+    [(); WIDTH * HEIGHT * PREFIX_SIZE + EOL_SIZE * HEIGHT]:,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a, const WIDTH: usize, const HEIGHT: usize, const BUFFERS: usize> ToString
     for BufferedCanvas<'a, WIDTH, HEIGHT, BUFFERS>
 where
@@ -108,10 +122,19 @@ impl<'a, const WIDTH: usize, const HEIGHT: usize, const BUFFERS: usize> Canvas
 where
     [(); WIDTH * HEIGHT * PREFIX_SIZE + EOL_SIZE * HEIGHT]:,
 {
-    type Writer = StdoutWriter<'a>;
     fn put_pixel(&mut self, colour: Colour, canvas_index: Vector2<usize>) {
         self.put_cell([b' '; 2], colour, canvas_index);
     }
+    fn get_pixel(&self, canvas_index: Vector2<usize>) -> Colour {
+        // read the decimal digits back out of the cell's style prefix
+        let prefix = &self._buffers[self._edit_index][Self::flatten_index(canvas_index)..];
+        let digits = |start: usize| {
+            prefix[start..start + 3]
+                .iter()
+                .fold(0, |n, digit| n * 10 + (digit - b'0'))
+        };
+        Colour::new(digits(7), digits(11), digits(15))
+    }
     fn fill(&mut self, colour: Colour) {
         for y in 0..HEIGHT {
             for x in 0..WIDTH {
@@ -122,6 +145,7 @@ where
 
     fn put_cell(&mut self, cell: [u8; 2], colour: Colour, canvas_index: Vector2<usize>) {
         // split colour into decimal digits
+        let colour = self.depth.quantise(colour);
         let ns: [[u8; 3]; 3] = [colour.x, colour.y, colour.z].map(|b| super::btod(b));
         // cast buffer pointer into a pointer of different type so we can do std::mem::replace
         // in reality this is safe so long as the buffer size doesn't change and if it does,
@@ -144,6 +168,14 @@ where
         );
     }
 
+    fn colour_depth(&self) -> ColourDepth {
+        self.depth
+    }
+
+    fn set_colour_depth(&mut self, depth: ColourDepth) {
+        self.depth = depth;
+    }
+
     fn write(&mut self, str: String, colour: Colour, canvas_index: Vector2<usize>) {
         // we just construct a new string with another square
         // of padding if not even length, this is because cells
@@ -166,7 +198,7 @@ where
         Vector2::new(WIDTH, HEIGHT)
     }
 
-    fn display(&self, out: &mut dyn Write) -> Result<(), std::io::Error> {
+    fn display(&mut self, out: &mut dyn Write) -> Result<(), std::io::Error> {
         out.write_all(&self._buffers[self._display_index])
     }
 }
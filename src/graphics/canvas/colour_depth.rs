@@ -0,0 +1,145 @@
+use std::io::Write;
+
+use crate::graphics::Colour;
+
+/// Colours a terminal can display, colours are quantised
+/// to the nearest in the palette when not `TrueColour`
+/// and aren't output at all when `Monochrome`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColourDepth {
+    /// 24-bit colour, `ESC[48;2;r;g;bm`
+    #[default]
+    TrueColour,
+    /// The xterm 6x6x6 colour cube and greyscale ramp, `ESC[48;5;nm`
+    Ansi256,
+    /// The standard and bright colours, `ESC[40m` to `ESC[107m`
+    Ansi16,
+    /// No colour, for terminals without it or for logs
+    Monochrome,
+}
+
+/// The standard and bright colours as xterm displays them
+const ANSI_16: [Colour; 16] = [
+    Colour::new(0, 0, 0),
+    Colour::new(205, 0, 0),
+    Colour::new(0, 205, 0),
+    Colour::new(205, 205, 0),
+    Colour::new(0, 0, 238),
+    Colour::new(205, 0, 205),
+    Colour::new(0, 205, 205),
+    Colour::new(229, 229, 229),
+    Colour::new(127, 127, 127),
+    Colour::new(255, 0, 0),
+    Colour::new(0, 255, 0),
+    Colour::new(255, 255, 0),
+    Colour::new(92, 92, 255),
+    Colour::new(255, 0, 255),
+    Colour::new(0, 255, 255),
+    Colour::new(255, 255, 255),
+];
+
+/// Channel levels of the 256 colour cube
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColourDepth {
+    /// Detects the colour depth of the terminal from `COLORTERM` and `TERM`
+    pub fn detect() -> Self {
+        let colour_term = std::env::var("COLORTERM").unwrap_or_default();
+        if colour_term == "truecolor" || colour_term == "24bit" {
+            return Self::TrueColour;
+        }
+        match std::env::var("TERM") {
+            Ok(term) if term == "dumb" => Self::Monochrome,
+            Ok(term) if term.contains("256") => Self::Ansi256,
+            Ok(_) => Self::Ansi16,
+            // windows consoles don't set `TERM` and support truecolour
+            Err(_) if cfg!(windows) => Self::TrueColour,
+            Err(_) => Self::Ansi16,
+        }
+    }
+
+    /// Parses a colour depth by name, `truecolour`, `256`, `16` or `none`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "truecolour" | "truecolor" | "24bit" => Some(Self::TrueColour),
+            "256" => Some(Self::Ansi256),
+            "16" => Some(Self::Ansi16),
+            "none" | "monochrome" => Some(Self::Monochrome),
+            _ => None,
+        }
+    }
+
+    /// Returns the palette index and colour nearest to `colour`,
+    /// the index is meaningless for `TrueColour` and `Monochrome`
+    pub fn nearest(&self, colour: Colour) -> (u8, Colour) {
+        match self {
+            Self::TrueColour | Self::Monochrome => (0, colour),
+            Self::Ansi256 => {
+                let level = |c: u8| {
+                    (0..CUBE_LEVELS.len())
+                        .min_by_key(|&i| CUBE_LEVELS[i].abs_diff(c))
+                        .unwrap()
+                };
+                let (r, g, b) = (level(colour.x), level(colour.y), level(colour.z));
+                let cube = Colour::new(CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+
+                // the greyscale ramp runs from 8 to 238 in steps of 10
+                let mean = (colour.x as u16 + colour.y as u16 + colour.z as u16) / 3;
+                let step = (mean.saturating_sub(3) / 10).min(23) as u8;
+                let grey = Colour::from_element(8 + 10 * step);
+
+                if distance(colour, grey) < distance(colour, cube) {
+                    (232 + step, grey)
+                } else {
+                    ((16 + 36 * r + 6 * g + b) as u8, cube)
+                }
+            }
+            Self::Ansi16 => ANSI_16
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, &c)| distance(colour, c))
+                .map(|(i, &c)| (i as u8, c))
+                .unwrap(),
+        }
+    }
+
+    /// Returns the colour in the palette nearest to `colour`
+    #[inline]
+    pub fn quantise(&self, colour: Colour) -> Colour {
+        self.nearest(colour).1
+    }
+
+    /// Writes the SGR parameters selecting `colour` as the foreground
+    /// or background, without the leading `ESC[` or trailing `m`.
+    /// Writes nothing when `Monochrome`.
+    pub(super) fn write_parameters(
+        &self,
+        out: &mut dyn Write,
+        colour: Colour,
+        background: bool,
+    ) -> Result<(), std::io::Error> {
+        let layer = if background { 48 } else { 38 };
+        match self {
+            Self::TrueColour => write!(out, "{};2;{};{};{}", layer, colour.x, colour.y, colour.z),
+            Self::Ansi256 => write!(out, "{};5;{}", layer, self.nearest(colour).0),
+            Self::Ansi16 => {
+                let index = self.nearest(colour).0;
+                let base = match (background, index < 8) {
+                    (false, true) => 30,
+                    (false, false) => 90 - 8,
+                    (true, true) => 40,
+                    (true, false) => 100 - 8,
+                };
+                write!(out, "{}", base + index)
+            }
+            Self::Monochrome => Ok(()),
+        }
+    }
+}
+
+/// Squared distance between two colours
+#[inline]
+fn distance(a: Colour, b: Colour) -> u32 {
+    let d = a.cast::<i32>() - b.cast::<i32>();
+    d.dot(&d) as u32
+}
@@ -0,0 +1,88 @@
+use nalgebra::{Vector2, Vector3};
+
+use crate::graphics::Colour;
+
+use super::ColourDepth;
+
+/// How a frame is dithered when quantised to a palette,
+/// trades banding for noise
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Dither {
+    /// Each pixel is quantised to its nearest colour
+    #[default]
+    None,
+    /// Pixels are offset by a 4x4 Bayer threshold matrix before
+    /// quantising, a stable pattern that doesn't crawl as the camera moves
+    Bayer,
+    /// The quantisation error of each pixel is spread to its neighbours
+    /// to the right and below, smoother but noisier in motion
+    FloydSteinberg,
+}
+
+/// 4x4 Bayer threshold matrix, values are 0 to 15
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+impl Dither {
+    /// Parses a dither by name, `none`, `bayer` or `floyd-steinberg`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Self::None),
+            "bayer" => Some(Self::Bayer),
+            "floyd-steinberg" => Some(Self::FloydSteinberg),
+            _ => None,
+        }
+    }
+
+    /// Dithers a frame of `size` pixels, row by row, in place,
+    /// pixels are quantised to the colours of `depth`
+    pub fn apply(&self, pixels: &mut [Colour], size: Vector2<usize>, depth: ColourDepth) {
+        if depth == ColourDepth::TrueColour || depth == ColourDepth::Monochrome {
+            return;
+        }
+        match self {
+            Self::None => {
+                for pixel in pixels.iter_mut() {
+                    *pixel = depth.quantise(*pixel);
+                }
+            }
+            Self::Bayer => {
+                // roughly the distance between neighbouring palette colours
+                let spread = match depth {
+                    ColourDepth::Ansi256 => 40.0,
+                    _ => 128.0,
+                };
+                for y in 0..size.y {
+                    for x in 0..size.x {
+                        let threshold = (BAYER[y % 4][x % 4] as f64 + 0.5) / 16.0 - 0.5;
+                        let pixel = &mut pixels[x + y * size.x];
+                        *pixel = depth.quantise(
+                            pixel.map(|c| (c as f64 + threshold * spread).clamp(0.0, 255.0) as u8),
+                        );
+                    }
+                }
+            }
+            Self::FloydSteinberg => {
+                // error carried to the current and next row
+                let mut errors = vec![Vector3::<f64>::zeros(); 2 * (size.x + 2)];
+                let (current, next) = errors.split_at_mut(size.x + 2);
+                let (mut current, mut next) = (current, next);
+                for y in 0..size.y {
+                    for x in 0..size.x {
+                        let pixel = &mut pixels[x + y * size.x];
+                        let wanted =
+                            (pixel.cast::<f64>() + current[x + 1]).map(|c| c.clamp(0.0, 255.0));
+                        *pixel = depth.quantise(wanted.map(|c| c.round() as u8));
+                        let error = wanted - pixel.cast::<f64>();
+
+                        current[x + 2] += error * (7.0 / 16.0);
+                        next[x] += error * (3.0 / 16.0);
+                        next[x + 1] += error * (5.0 / 16.0);
+                        next[x + 2] += error * (1.0 / 16.0);
+                    }
+                    std::mem::swap(&mut current, &mut next);
+                    next.fill(Vector3::zeros());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::io::{self, BufRead, Write};
+
+use nalgebra::Vector2;
+
+use crate::graphics::Colour;
+
+use super::{Canvas, MemoryCanvas};
+
+/// Image formats a canvas can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Binary portable pixmap, `P6`
+    Ppm,
+    /// PNG, stored without compression
+    Png,
+}
+
+impl ImageFormat {
+    /// Parses an image format by name, `ppm` or `png`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ppm" => Some(Self::Ppm),
+            "png" => Some(Self::Png),
+            _ => None,
+        }
+    }
+
+    /// File extension of the format, without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Ppm => "ppm",
+            Self::Png => "png",
+        }
+    }
+}
+
+/// Writes the pixels of `canvas` as an image to `out`,
+/// the characters of text on the canvas aren't drawn
+pub fn export<C: Canvas + ?Sized>(
+    canvas: &C,
+    format: ImageFormat,
+    out: &mut dyn Write,
+) -> Result<(), io::Error> {
+    match format {
+        ImageFormat::Ppm => write_ppm(canvas, out),
+        ImageFormat::Png => write_png(canvas, out),
+    }
+}
+
+/// Returns a row of the canvas as RGB bytes
+fn row<C: Canvas + ?Sized>(canvas: &C, y: usize) -> Vec<u8> {
+    (0..canvas.size().x)
+        .flat_map(|x| {
+            let colour = canvas.get_pixel(Vector2::new(x, y));
+            [colour.x, colour.y, colour.z]
+        })
+        .collect()
+}
+
+/// Writes the pixels of `canvas` as a binary PPM image
+pub fn write_ppm<C: Canvas + ?Sized>(canvas: &C, out: &mut dyn Write) -> Result<(), io::Error> {
+    let size = canvas.size();
+    write!(out, "P6\n{} {}\n255\n", size.x, size.y)?;
+    for y in 0..size.y {
+        out.write_all(&row(canvas, y))?;
+    }
+    Ok(())
+}
+
+/// Reads a binary PPM image with 8-bit channels into a canvas,
+/// used to compare renders against golden images
+pub fn read_ppm(input: &mut dyn BufRead) -> Result<MemoryCanvas, io::Error> {
+    let invalid = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, reason.to_string());
+
+    // the header is 4 whitespace separated tokens, `#` starts a comment
+    let mut tokens = Vec::new();
+    while tokens.len() < 4 {
+        let mut token = Vec::new();
+        loop {
+            let mut byte = [0];
+            input.read_exact(&mut byte)?;
+            match byte[0] {
+                b'#' => {
+                    input.read_until(b'\n', &mut Vec::new())?;
+                }
+                b if b.is_ascii_whitespace() => {
+                    if !token.is_empty() {
+                        break;
+                    }
+                }
+                b => token.push(b),
+            }
+        }
+        tokens.push(String::from_utf8(token).map_err(|_| invalid("header isn't ascii"))?);
+    }
+    if tokens[0] != "P6" {
+        return Err(invalid("not a binary PPM image"));
+    }
+    let parse = |token: &str| {
+        token
+            .parse::<usize>()
+            .map_err(|_| invalid("header values must be numbers"))
+    };
+    let size = Vector2::new(parse(&tokens[1])?, parse(&tokens[2])?);
+    if parse(&tokens[3])? != 255 {
+        return Err(invalid("only 8-bit channels are supported"));
+    }
+
+    let mut canvas = MemoryCanvas::new(size);
+    let mut pixel = [0; 3];
+    for y in 0..size.y {
+        for x in 0..size.x {
+            input.read_exact(&mut pixel)?;
+            canvas.put_pixel(Colour::from(pixel), Vector2::new(x, y));
+        }
+    }
+    Ok(canvas)
+}
+
+/// Writes the pixels of `canvas` as a PNG image, the image data
+/// is stored rather than compressed to avoid depending on zlib
+pub fn write_png<C: Canvas + ?Sized>(canvas: &C, out: &mut dyn Write) -> Result<(), io::Error> {
+    let size = canvas.size();
+    out.write_all(b"\x89PNG\r\n\x1A\n")?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(size.x as u32).to_be_bytes());
+    header.extend_from_slice(&(size.y as u32).to_be_bytes());
+    // 8-bit RGB, default compression and filtering, not interlaced
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_chunk(out, b"IHDR", &header)?;
+
+    // each row is preceded by its filter type, none
+    let mut data = Vec::with_capacity((3 * size.x + 1) * size.y);
+    for y in 0..size.y {
+        data.push(0);
+        data.extend_from_slice(&row(canvas, y));
+    }
+    write_chunk(out, b"IDAT", &zlib_stored(&data))?;
+    write_chunk(out, b"IEND", &[])
+}
+
+/// Writes a PNG chunk, its length, type, data and checksum
+fn write_chunk(out: &mut dyn Write, kind: &[u8; 4], data: &[u8]) -> Result<(), io::Error> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    out.write_all(&crc32(kind.iter().chain(data)).to_be_bytes())
+}
+
+/// Wraps `data` in a zlib stream of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = u16::MAX as usize;
+
+    let blocks = data.len().div_ceil(MAX_BLOCK).max(1);
+    let mut stream = Vec::with_capacity(data.len() + 5 * blocks + 6);
+    // deflate with a 32KiB window, no preset dictionary
+    stream.extend_from_slice(&[0x78, 0x01]);
+    for i in 0..blocks {
+        let block = &data[i * MAX_BLOCK..((i + 1) * MAX_BLOCK).min(data.len())];
+        let len = block.len() as u16;
+        stream.push((i == blocks - 1) as u8);
+        stream.extend_from_slice(&len.to_le_bytes());
+        stream.extend_from_slice(&(!len).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+/// CRC-32 as PNG uses it
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Adler-32 as zlib uses it
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
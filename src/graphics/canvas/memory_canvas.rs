@@ -0,0 +1,124 @@
+use std::io::Write;
+
+use nalgebra::Vector2;
+
+use crate::graphics::Colour;
+
+use super::{Canvas, ColourDepth};
+
+/// A headless canvas, stores the colour and glyphs of every
+/// cell in memory rather than as escape codes, so frames can
+/// be inspected without a terminal
+pub struct MemoryCanvas {
+    size: Vector2<usize>,
+    pixels: Vec<Colour>,
+    cells: Vec<[u8; 2]>,
+    depth: ColourDepth,
+}
+
+impl MemoryCanvas {
+    /// Creates a cleared canvas of the given size
+    pub fn new(size: Vector2<usize>) -> Self {
+        Self {
+            size,
+            pixels: vec![Colour::zeros(); size.x * size.y],
+            cells: vec![[b' '; 2]; size.x * size.y],
+            depth: ColourDepth::TrueColour,
+        }
+    }
+
+    /// Reallocates the canvas to a new size, clearing it
+    pub fn resize(&mut self, size: Vector2<usize>) {
+        *self = Self {
+            depth: self.depth,
+            ..Self::new(size)
+        };
+    }
+
+    #[inline]
+    /// Flattens the passed position to an index into the cell storage
+    fn flatten_index(&self, canvas_index: Vector2<usize>) -> usize {
+        canvas_index.x + canvas_index.y * self.size.x
+    }
+
+    /// Returns the two characters of the cell at `canvas_index`
+    pub fn get_cell(&self, canvas_index: Vector2<usize>) -> [u8; 2] {
+        self.cells[self.flatten_index(canvas_index)]
+    }
+}
+
+impl Canvas for MemoryCanvas {
+    fn put_pixel(&mut self, colour: Colour, canvas_index: Vector2<usize>) {
+        self.put_cell([b' '; 2], colour, canvas_index);
+    }
+
+    fn get_pixel(&self, canvas_index: Vector2<usize>) -> Colour {
+        self.pixels[self.flatten_index(canvas_index)]
+    }
+
+    fn fill(&mut self, colour: Colour) {
+        self.pixels.fill(colour);
+        self.cells.fill([b' '; 2]);
+    }
+
+    fn clear(&mut self) {
+        self.fill(Colour::zeros());
+    }
+
+    fn size(&self) -> Vector2<usize> {
+        self.size
+    }
+
+    fn display(&mut self, out: &mut dyn Write) -> Result<(), std::io::Error> {
+        for row in self
+            .pixels
+            .chunks(self.size.x)
+            .zip(self.cells.chunks(self.size.x))
+        {
+            let coloured = self.depth != ColourDepth::Monochrome;
+            for (colour, cell) in row.0.iter().zip(row.1) {
+                if coloured {
+                    out.write_all(b"\x1B[")?;
+                    self.depth.write_parameters(out, *colour, true)?;
+                    out.write_all(b"m")?;
+                }
+                out.write_all(cell)?;
+            }
+            if coloured {
+                out.write_all(b"\x1B[0m")?;
+            }
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn colour_depth(&self) -> ColourDepth {
+        self.depth
+    }
+
+    fn set_colour_depth(&mut self, depth: ColourDepth) {
+        self.depth = depth;
+    }
+
+    fn write(&mut self, str: String, colour: Colour, canvas_index: Vector2<usize>) {
+        // cells are 2-chars wide, pad odd length strings
+        let mut adj_str = str;
+        if adj_str.len() % 2 == 1 {
+            adj_str += " ";
+        }
+        for (i, cell) in adj_str.bytes().array_chunks::<2>().enumerate() {
+            let position = canvas_index + Vector2::new(i, 0);
+            // text running off the edge is clipped
+            if position.x >= self.size.x || position.y >= self.size.y {
+                break;
+            }
+            self.put_cell(cell, colour, position);
+        }
+    }
+
+    fn put_cell(&mut self, cell: [u8; 2], colour: Colour, canvas_index: Vector2<usize>) {
+        let idx = self.flatten_index(canvas_index);
+        self.pixels[idx] = colour;
+        self.cells[idx] = cell;
+    }
+}
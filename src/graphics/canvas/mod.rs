@@ -1,4 +1,12 @@
 mod buffered_canvas;
+mod colour_depth;
+mod dither;
+mod export;
+mod memory_canvas;
+mod mode;
+mod presenter;
+mod swap_chain;
+mod terminal_canvas;
 mod test;
 use std::io::Write;
 
@@ -7,8 +15,16 @@ use nalgebra::Vector2;
 
 use crate::graphics::Colour;
 
-#[allow(unused_imports)]
 pub use self::buffered_canvas::BufferedCanvas;
+pub use self::colour_depth::ColourDepth;
+pub use self::dither::Dither;
+pub use self::export::{export, ImageFormat};
+pub use self::export::{read_ppm, write_png, write_ppm};
+pub use self::memory_canvas::MemoryCanvas;
+pub use self::mode::{Ascii, Blocks, Braille, CellMode, HalfBlocks, Quadrants};
+pub use self::presenter::{Glyph, PresentStatistics, Presenter};
+pub use self::swap_chain::SwapChain;
+pub use self::terminal_canvas::TerminalCanvas;
 
 /// Converts a byte, i.e. `255` to its digits in base ten: [b'2', b'5', b'5']
 /// or `10` to [b'0', b'1', b'0']
@@ -20,17 +36,32 @@ pub(self) fn btod(num: u8) -> [u8; 3] {
 }
 
 pub trait Canvas {
-    type Writer: Write;
     /// Puts a pixel to the canvas, is an empty cell in reality
     fn put_pixel(&mut self, colour: Colour, position: Vector2<usize>);
+    /// Returns the colour of a pixel on the canvas
+    fn get_pixel(&self, position: Vector2<usize>) -> Colour;
     /// Fills the canvas with empty cells of given colour
     fn fill(&mut self, colour: Colour);
     /// Fills the canvas with black
     fn clear(&mut self);
     /// Returns the size of the canvas
     fn size(&self) -> Vector2<usize>;
+    /// Returns the size of the canvas in cells of text,
+    /// `write` and `put_cell` are positioned in these cells
+    fn text_size(&self) -> Vector2<usize> {
+        self.size()
+    }
+    /// Returns the width of a pixel relative to its height as displayed
+    fn pixel_aspect(&self) -> f64 {
+        1.0
+    }
+    /// Returns the colour depth the canvas is displayed with
+    fn colour_depth(&self) -> ColourDepth;
+    /// Sets the colour depth the canvas is displayed with,
+    /// colours are quantised to the nearest in its palette
+    fn set_colour_depth(&mut self, depth: ColourDepth);
     /// Displays the canvas to `out`
-    fn display(&self, out: &mut dyn Write) -> Result<(), std::io::Error>;
+    fn display(&mut self, out: &mut dyn Write) -> Result<(), std::io::Error>;
     /// Write a string to the canvas, is split into cells
     fn write(&mut self, str: String, colour: Colour, canvas_index: Vector2<usize>);
     /// Put a single cell of two characters to the canvas
@@ -0,0 +1,207 @@
+use nalgebra::{Vector2, Vector3};
+
+use crate::graphics::Colour;
+
+use super::Glyph;
+
+/// Defines how a `TerminalCanvas` draws its pixels, a block
+/// of pixels is packed into a run of glyphs on a single row
+pub trait CellMode {
+    /// Pixels packed into a block, columns by rows
+    const PIXELS: Vector2<usize>;
+    /// Terminal columns a block is drawn with
+    const COLUMNS: usize;
+
+    /// Packs a block of pixels, given row by row, into its glyphs
+    fn pack(&self, pixels: &[Colour], glyphs: &mut [Glyph]);
+}
+
+/// Each pixel is an empty 2-char cell of its colour
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Blocks;
+
+impl CellMode for Blocks {
+    const PIXELS: Vector2<usize> = Vector2::new(1, 1);
+    const COLUMNS: usize = 2;
+
+    fn pack(&self, pixels: &[Colour], glyphs: &mut [Glyph]) {
+        glyphs.fill(Glyph {
+            char: ' ',
+            foreground: None,
+            background: pixels[0],
+        });
+    }
+}
+
+/// Two vertically stacked pixels per character using `▀`, the top
+/// pixel is the foreground and the bottom the background. Doubles
+/// the vertical resolution of `Blocks` and keeps pixels square.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HalfBlocks;
+
+impl CellMode for HalfBlocks {
+    const PIXELS: Vector2<usize> = Vector2::new(1, 2);
+    const COLUMNS: usize = 1;
+
+    fn pack(&self, pixels: &[Colour], glyphs: &mut [Glyph]) {
+        glyphs[0] = Glyph {
+            char: '▀',
+            foreground: Some(pixels[0]),
+            background: pixels[1],
+        };
+    }
+}
+
+/// Four pixels per character in a 2x2 grid using the quadrant
+/// block characters, drawn in the two colours that best fit them
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Quadrants;
+
+impl Quadrants {
+    /// Quadrant characters indexed by their set quadrants, bits are
+    /// top-left, top-right, bottom-left then bottom-right
+    const CHARS: [char; 16] = [
+        ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+    ];
+}
+
+impl CellMode for Quadrants {
+    const PIXELS: Vector2<usize> = Vector2::new(2, 2);
+    const COLUMNS: usize = 1;
+
+    fn pack(&self, pixels: &[Colour], glyphs: &mut [Glyph]) {
+        let (mask, foreground, background) = two_colour_fit(pixels);
+        glyphs[0] = glyph(Self::CHARS[mask as usize], mask, foreground, background);
+    }
+}
+
+/// Eight pixels per character in a 2x4 grid using braille
+/// characters, drawn in the two colours that best fit them.
+/// Dots are small so this suits detailed, high contrast scenes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Braille;
+
+impl Braille {
+    /// Dot bits of each pixel, row by row
+    const DOTS: [u8; 8] = [0x01, 0x08, 0x02, 0x10, 0x04, 0x20, 0x40, 0x80];
+}
+
+impl CellMode for Braille {
+    const PIXELS: Vector2<usize> = Vector2::new(2, 4);
+    const COLUMNS: usize = 1;
+
+    fn pack(&self, pixels: &[Colour], glyphs: &mut [Glyph]) {
+        let (mask, foreground, background) = two_colour_fit(pixels);
+        let dots = Self::DOTS
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| mask & 1 << i != 0)
+            .fold(0, |dots, (_, dot)| dots | dot);
+        let char = char::from_u32(0x2800 + dots as u32).unwrap();
+        glyphs[0] = glyph(char, mask, foreground, background);
+    }
+}
+
+/// Builds a glyph for a fitted block, blocks with no
+/// foreground pixels are drawn as a space
+#[inline]
+fn glyph(char: char, mask: u8, foreground: Colour, background: Colour) -> Glyph {
+    if mask == 0 {
+        Glyph {
+            char: ' ',
+            foreground: None,
+            background,
+        }
+    } else {
+        Glyph {
+            char,
+            foreground: Some(foreground),
+            background,
+        }
+    }
+}
+
+/// Splits up to 8 pixels into two colours, returning the mask of
+/// pixels in the foreground and the mean colour of each side.
+/// Pixels are split at the middle of the channel with the widest
+/// range, those above it are the foreground.
+fn two_colour_fit(pixels: &[Colour]) -> (u8, Colour, Colour) {
+    let min = pixels
+        .iter()
+        .fold(Colour::from_element(255), |min, p| min.inf(p));
+    let max = pixels.iter().fold(Colour::zeros(), |max, p| max.sup(p));
+    let range = max - min;
+    let channel = range.imax();
+    if range[channel] == 0 {
+        return (0, pixels[0], pixels[0]);
+    }
+
+    // halfway, rounded down, so neither side is empty
+    let threshold = min[channel] + range[channel] / 2;
+    let mask = pixels
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p[channel] > threshold)
+        .fold(0u8, |mask, (i, _)| mask | 1 << i);
+
+    let mean = |foreground: bool| {
+        let (sum, count) = pixels
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (mask & 1 << i != 0) == foreground)
+            .fold((Vector3::<u32>::zeros(), 0), |(sum, count), (_, p)| {
+                (sum + p.cast::<u32>(), count + 1)
+            });
+        (sum / count).map(|c| c as u8)
+    };
+    (mask, mean(true), mean(false))
+}
+
+/// A character per pixel chosen from a ramp by the pixel's
+/// luminance, for terminals without colour, use with
+/// `ColourDepth::Monochrome` so no colours are output at all
+#[derive(Debug, Clone)]
+pub struct Ascii {
+    /// Characters from darkest to brightest
+    ramp: Vec<char>,
+}
+
+impl Ascii {
+    /// Creates a mode using `ramp`, characters from darkest to brightest
+    pub fn with_ramp(ramp: &str) -> Self {
+        assert!(
+            !ramp.is_empty(),
+            "an ascii ramp needs at least one character"
+        );
+        Self {
+            ramp: ramp.chars().collect(),
+        }
+    }
+
+    /// Returns the character of the ramp for `colour`'s luminance
+    pub fn char(&self, colour: Colour) -> char {
+        let luminance =
+            (0.2126 * colour.x as f64 + 0.7152 * colour.y as f64 + 0.0722 * colour.z as f64)
+                / 255.0;
+        self.ramp[(luminance * (self.ramp.len() - 1) as f64).round() as usize]
+    }
+}
+
+impl Default for Ascii {
+    fn default() -> Self {
+        Self::with_ramp(" .:-=+*#%@")
+    }
+}
+
+impl CellMode for Ascii {
+    const PIXELS: Vector2<usize> = Vector2::new(1, 1);
+    const COLUMNS: usize = 1;
+
+    fn pack(&self, pixels: &[Colour], glyphs: &mut [Glyph]) {
+        glyphs[0] = Glyph {
+            char: self.char(pixels[0]),
+            foreground: None,
+            background: Colour::zeros(),
+        };
+    }
+}
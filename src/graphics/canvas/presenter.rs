@@ -0,0 +1,188 @@
+use std::io::Write;
+
+use nalgebra::Vector2;
+
+use crate::graphics::Colour;
+
+use super::ColourDepth;
+
+/// A single terminal character as it is presented,
+/// a foreground of `None` uses the terminal's default
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyph {
+    pub char: char,
+    pub foreground: Option<Colour>,
+    pub background: Colour,
+}
+
+impl Default for Glyph {
+    fn default() -> Self {
+        Self {
+            char: ' ',
+            foreground: None,
+            background: Colour::zeros(),
+        }
+    }
+}
+
+/// Output statistics of the last presented frame
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PresentStatistics {
+    /// Bytes written for the last frame
+    pub bytes: usize,
+    /// Glyphs rewritten for the last frame
+    pub changed: usize,
+    /// Bytes written over all frames
+    pub total_bytes: usize,
+}
+
+/// Writes screens of glyphs to the terminal, when differential
+/// only the glyphs that changed since the last presented
+/// frame are written
+pub struct Presenter {
+    size: Vector2<usize>,
+    previous: Vec<Glyph>,
+    /// Whether `previous` is on screen, if not the
+    /// next frame is written in full
+    valid: bool,
+    differential: bool,
+    depth: ColourDepth,
+    statistics: PresentStatistics,
+}
+
+/// Counts the bytes written through it
+struct CountingWriter<'a> {
+    inner: &'a mut dyn Write,
+    bytes: usize,
+}
+
+impl<'a> Write for CountingWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes += written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl Presenter {
+    /// Creates a presenter for a screen of `size` glyphs,
+    /// the first frame is always written in full
+    pub fn new(size: Vector2<usize>) -> Self {
+        Self {
+            size,
+            previous: vec![Glyph::default(); size.x * size.y],
+            valid: false,
+            differential: true,
+            depth: ColourDepth::TrueColour,
+            statistics: PresentStatistics::default(),
+        }
+    }
+
+    /// Resizes the screen to `size` glyphs, keeping the settings,
+    /// the next frame is written in full
+    pub fn resize(&mut self, size: Vector2<usize>) {
+        self.size = size;
+        self.previous = vec![Glyph::default(); size.x * size.y];
+        self.valid = false;
+    }
+
+    /// Sets whether only changed glyphs are written
+    pub fn set_differential(&mut self, differential: bool) {
+        self.differential = differential;
+    }
+
+    /// Sets the colour depth glyphs are written with,
+    /// the next frame is written in full
+    pub fn set_colour_depth(&mut self, depth: ColourDepth) {
+        self.depth = depth;
+        self.valid = false;
+    }
+
+    #[inline]
+    pub fn colour_depth(&self) -> ColourDepth {
+        self.depth
+    }
+
+    #[inline]
+    pub fn statistics(&self) -> &PresentStatistics {
+        &self.statistics
+    }
+
+    /// Writes `screen` to `out`, `screen` must be the presenter's size
+    pub fn present(&mut self, screen: &[Glyph], out: &mut dyn Write) -> Result<(), std::io::Error> {
+        assert_eq!(screen.len(), self.previous.len());
+
+        let full = !(self.valid && self.differential);
+        let mut out = CountingWriter {
+            inner: out,
+            bytes: 0,
+        };
+        let mut changed = 0;
+        // the style and position the terminal is currently in, `None` if unknown
+        let mut style: Option<(Option<Colour>, Colour)> = None;
+        let mut cursor: Option<Vector2<usize>> = None;
+
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                let idx = x + y * self.size.x;
+                let glyph = screen[idx];
+                if !full && glyph == self.previous[idx] {
+                    continue;
+                }
+                changed += 1;
+
+                let position = Vector2::new(x, y);
+                if cursor != Some(position) {
+                    write!(out, "\x1B[{};{}H", y + 1, x + 1)?;
+                }
+                // runs of the same colour share a single style sequence
+                let glyph_style = (
+                    glyph.foreground.map(|fg| self.depth.quantise(fg)),
+                    self.depth.quantise(glyph.background),
+                );
+                if style != Some(glyph_style) {
+                    write_style(&mut out, self.depth, glyph_style.0, glyph_style.1)?;
+                    style = Some(glyph_style);
+                }
+                write!(out, "{}", glyph.char)?;
+                // the cursor doesn't wrap, so is unknown after the last column
+                cursor = (x + 1 < self.size.x).then(|| Vector2::new(x + 1, y));
+            }
+        }
+        if style.is_some() && self.depth != ColourDepth::Monochrome {
+            out.write_all(b"\x1B[0m")?;
+        }
+
+        self.previous.copy_from_slice(screen);
+        self.valid = true;
+        self.statistics.bytes = out.bytes;
+        self.statistics.changed = changed;
+        self.statistics.total_bytes += out.bytes;
+        Ok(())
+    }
+}
+
+/// Writes the SGR sequence selecting the given colours,
+/// a foreground of `None` selects the terminal's default
+pub(super) fn write_style(
+    out: &mut dyn Write,
+    depth: ColourDepth,
+    foreground: Option<Colour>,
+    background: Colour,
+) -> Result<(), std::io::Error> {
+    if depth == ColourDepth::Monochrome {
+        return Ok(());
+    }
+    out.write_all(b"\x1B[")?;
+    match foreground {
+        Some(fg) => depth.write_parameters(out, fg, false)?,
+        None => out.write_all(b"39")?,
+    }
+    out.write_all(b";")?;
+    depth.write_parameters(out, background, true)?;
+    out.write_all(b"m")
+}
@@ -0,0 +1,116 @@
+use std::{
+    io::{self, Write},
+    sync::mpsc::{self, Receiver, Sender},
+    thread::{self, JoinHandle},
+};
+
+/// Writes frames to the terminal on a dedicated thread, so the next
+/// frame is rendered while the last is still being written.
+/// Frames are rendered into the back buffer then swapped, a buffer
+/// is owned by one thread at a time so a frame is never written while
+/// it is rendered, and frames are always written whole and in order.
+pub struct SwapChain {
+    /// The buffer the next frame is rendered into
+    back: Vec<u8>,
+    /// Frames waiting to be written
+    frames: Option<Sender<Vec<u8>>>,
+    /// Buffers the thread has finished writing
+    free: Receiver<Vec<u8>>,
+    thread: Option<JoinHandle<Result<(), io::Error>>>,
+}
+
+impl SwapChain {
+    /// Starts a thread writing frames to `out`, `buffers` frames
+    /// can be in use at once, one rendered and the rest queued or
+    /// being written. With 1 buffer swapping waits for the frame
+    /// to be written, 2 is double buffering and 3 triple buffering.
+    pub fn new<W: Write + Send + 'static>(mut out: W, buffers: usize) -> Self {
+        assert!(buffers > 0, "a swap chain needs at least one buffer");
+        let (frames, queued) = mpsc::channel::<Vec<u8>>();
+        let (written, free) = mpsc::channel();
+        for _ in 1..buffers {
+            written.send(Vec::new()).unwrap();
+        }
+
+        let thread = thread::Builder::new()
+            .name("present".to_string())
+            .spawn(move || {
+                for mut frame in queued {
+                    out.write_all(&frame)?;
+                    out.flush()?;
+                    frame.clear();
+                    // the chain was dropped, nothing is left to write
+                    if written.send(frame).is_err() {
+                        break;
+                    }
+                }
+                Ok(())
+            })
+            .expect("failed to spawn the present thread");
+
+        Self {
+            back: Vec::new(),
+            frames: Some(frames),
+            free,
+            thread: Some(thread),
+        }
+    }
+
+    /// The buffer the next frame is rendered into
+    #[inline]
+    pub fn back(&mut self) -> &mut Vec<u8> {
+        &mut self.back
+    }
+
+    /// Queues the back buffer to be written and takes a free buffer
+    /// to render the next frame into, waits if every buffer is in use.
+    /// Returns the thread's error if it failed to write a frame.
+    pub fn swap(&mut self) -> Result<(), io::Error> {
+        let frame = std::mem::take(&mut self.back);
+        let sent = match &self.frames {
+            Some(frames) => frames.send(frame).is_ok(),
+            None => false,
+        };
+        match self.free.recv() {
+            Ok(buffer) if sent => {
+                self.back = buffer;
+                Ok(())
+            }
+            // the thread has stopped, so it failed to write
+            _ => Err(self.join()),
+        }
+    }
+
+    /// Writes the back buffer then waits for every frame to be written
+    pub fn finish(&mut self) -> Result<(), io::Error> {
+        if let Some(frames) = &self.frames {
+            let _ = frames.send(std::mem::take(&mut self.back));
+        }
+        self.frames = None;
+        match self.thread.take() {
+            Some(thread) => thread.join().unwrap_or_else(|_| Err(panicked())),
+            None => Ok(()),
+        }
+    }
+
+    /// Stops the thread and returns why it stopped
+    fn join(&mut self) -> io::Error {
+        self.frames = None;
+        match self.thread.take().map(|thread| thread.join()) {
+            Some(Ok(Err(err))) => err,
+            Some(Err(_)) => panicked(),
+            _ => io::Error::new(io::ErrorKind::BrokenPipe, "the present thread has stopped"),
+        }
+    }
+}
+
+impl Drop for SwapChain {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+#[inline]
+fn panicked() -> io::Error {
+    io::Error::other("the present thread panicked")
+}
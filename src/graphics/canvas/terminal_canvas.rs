@@ -0,0 +1,203 @@
+use std::io::Write;
+
+use crossterm::terminal;
+use nalgebra::Vector2;
+
+use crate::graphics::Colour;
+
+use super::{Blocks, Canvas, CellMode, ColourDepth, Dither, Glyph, PresentStatistics, Presenter};
+
+/// A canvas sized to the terminal at runtime, how pixels are
+/// drawn is decided by the `CellMode`. Text is drawn over the
+/// pixels in 2-char cells. Call `TerminalCanvas::resize` when
+/// the terminal is resized.
+/// Only glyphs that changed since the last frame are displayed.
+pub struct TerminalCanvas<M: CellMode = Blocks> {
+    mode: M,
+    /// Size in pixels
+    size: Vector2<usize>,
+    pixels: Vec<Colour>,
+    /// How pixels are dithered to the colour depth
+    dither: Dither,
+    /// Pixels after dithering, kept to avoid reallocating
+    dithered: Vec<Colour>,
+    /// Size in glyphs, columns by rows
+    screen_size: Vector2<usize>,
+    /// Text drawn over the pixels, per glyph
+    overlay: Vec<Option<Glyph>>,
+    /// Glyphs of the frame being displayed, kept to avoid reallocating
+    screen: Vec<Glyph>,
+    presenter: Presenter,
+}
+
+impl<M: CellMode + Default> Default for TerminalCanvas<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: CellMode + Default> TerminalCanvas<M> {
+    /// Creates a canvas filling the current terminal
+    pub fn new() -> Self {
+        Self::with_mode(M::default())
+    }
+}
+
+impl<M: CellMode> TerminalCanvas<M> {
+    /// Terminal size used when the terminal can't be queried
+    const FALLBACK_SIZE: Vector2<u16> = Vector2::new(160, 48);
+
+    /// Creates a canvas drawn with `mode` filling the current terminal
+    pub fn with_mode(mode: M) -> Self {
+        let (columns, rows) =
+            terminal::size().unwrap_or((Self::FALLBACK_SIZE.x, Self::FALLBACK_SIZE.y));
+        Self::with_size(mode, Vector2::new(columns, rows))
+    }
+
+    /// Creates a canvas drawn with `mode` filling a terminal
+    /// of `terminal_size` columns by rows
+    pub fn with_size(mode: M, terminal_size: Vector2<u16>) -> Self {
+        let mut this = Self {
+            mode,
+            size: Vector2::zeros(),
+            pixels: Vec::new(),
+            dither: Dither::None,
+            dithered: Vec::new(),
+            screen_size: Vector2::zeros(),
+            overlay: Vec::new(),
+            screen: Vec::new(),
+            presenter: Presenter::new(Vector2::zeros()),
+        };
+        this.resize(terminal_size);
+        this
+    }
+
+    /// Reallocates the canvas to fit a terminal of `terminal_size`
+    /// columns by rows, the canvas is cleared. It never overflows the
+    /// terminal, so rows don't wrap, but keeps at least one cell.
+    pub fn resize(&mut self, terminal_size: Vector2<u16>) {
+        let terminal_size = terminal_size.cast::<usize>();
+        let blocks =
+            Vector2::new(terminal_size.x / M::COLUMNS, terminal_size.y).sup(&Vector2::repeat(1));
+
+        self.size = blocks.component_mul(&M::PIXELS);
+        self.pixels = vec![Colour::zeros(); self.size.x * self.size.y];
+        self.dithered = self.pixels.clone();
+        self.screen_size = Vector2::new(blocks.x * M::COLUMNS, blocks.y);
+        self.overlay = vec![None; self.screen_size.x * self.screen_size.y];
+        self.screen = vec![Glyph::default(); self.screen_size.x * self.screen_size.y];
+        self.presenter.resize(self.screen_size);
+    }
+
+    /// Sets whether only the changed glyphs are displayed,
+    /// otherwise every frame is displayed in full
+    pub fn set_differential(&mut self, differential: bool) {
+        self.presenter.set_differential(differential);
+    }
+
+    /// Sets how pixels are dithered when the colour
+    /// depth is less than truecolour
+    pub fn set_dither(&mut self, dither: Dither) {
+        self.dither = dither;
+    }
+
+    /// Output statistics of the last displayed frame
+    pub fn statistics(&self) -> &PresentStatistics {
+        self.presenter.statistics()
+    }
+}
+
+impl<M: CellMode> Canvas for TerminalCanvas<M> {
+    fn put_pixel(&mut self, colour: Colour, position: Vector2<usize>) {
+        self.pixels[position.x + position.y * self.size.x] = colour;
+    }
+
+    fn get_pixel(&self, position: Vector2<usize>) -> Colour {
+        self.pixels[position.x + position.y * self.size.x]
+    }
+
+    fn fill(&mut self, colour: Colour) {
+        self.pixels.fill(colour);
+        self.overlay.fill(None);
+    }
+
+    fn clear(&mut self) {
+        self.fill(Colour::zeros());
+    }
+
+    fn size(&self) -> Vector2<usize> {
+        self.size
+    }
+
+    fn text_size(&self) -> Vector2<usize> {
+        Vector2::new(self.screen_size.x / 2, self.screen_size.y)
+    }
+
+    fn display(&mut self, out: &mut dyn Write) -> Result<(), std::io::Error> {
+        let depth = self.colour_depth();
+        self.dithered.copy_from_slice(&self.pixels);
+        self.dither.apply(&mut self.dithered, self.size, depth);
+
+        let mut block = vec![Colour::zeros(); M::PIXELS.x * M::PIXELS.y];
+        for y in 0..self.screen_size.y {
+            for x in 0..self.screen_size.x / M::COLUMNS {
+                // gather the block's pixels row by row
+                for py in 0..M::PIXELS.y {
+                    for px in 0..M::PIXELS.x {
+                        block[px + py * M::PIXELS.x] = self.dithered
+                            [x * M::PIXELS.x + px + (y * M::PIXELS.y + py) * self.size.x];
+                    }
+                }
+                let start = x * M::COLUMNS + y * self.screen_size.x;
+                self.mode
+                    .pack(&block, &mut self.screen[start..start + M::COLUMNS]);
+            }
+        }
+        for (glyph, text) in self.screen.iter_mut().zip(&self.overlay) {
+            if let Some(text) = text {
+                *glyph = *text;
+            }
+        }
+        self.presenter.present(&self.screen, out)
+    }
+
+    fn pixel_aspect(&self) -> f64 {
+        // terminal cells are about twice as tall as they're wide
+        (M::COLUMNS * M::PIXELS.y) as f64 / (2 * M::PIXELS.x) as f64
+    }
+
+    fn colour_depth(&self) -> ColourDepth {
+        self.presenter.colour_depth()
+    }
+
+    fn set_colour_depth(&mut self, depth: ColourDepth) {
+        self.presenter.set_colour_depth(depth);
+    }
+
+    fn write(&mut self, str: String, colour: Colour, canvas_index: Vector2<usize>) {
+        // cells are 2-chars wide, pad odd length strings
+        let mut adj_str = str;
+        if adj_str.len() % 2 == 1 {
+            adj_str += " ";
+        }
+        for (i, cell) in adj_str.bytes().array_chunks::<2>().enumerate() {
+            let position = canvas_index + Vector2::new(i, 0);
+            // text running off the edge is clipped
+            if position.x >= self.text_size().x || position.y >= self.text_size().y {
+                break;
+            }
+            self.put_cell(cell, colour, position);
+        }
+    }
+
+    fn put_cell(&mut self, cell: [u8; 2], colour: Colour, canvas_index: Vector2<usize>) {
+        let start = 2 * canvas_index.x + canvas_index.y * self.screen_size.x;
+        for (text, char) in self.overlay[start..start + 2].iter_mut().zip(cell) {
+            *text = Some(Glyph {
+                char: char as char,
+                foreground: None,
+                background: colour,
+            });
+        }
+    }
+}
@@ -29,3 +29,542 @@ fn test_eols() {
         }
     }
 }
+
+#[test]
+fn test_memory_canvas_pixels() {
+    let mut canvas = MemoryCanvas::new(Vector2::new(8, 4));
+    canvas.put_pixel(Colour::new(255, 255, 100), Vector2::new(3, 2));
+
+    assert_eq!(
+        canvas.get_pixel(Vector2::new(3, 2)),
+        Colour::new(255, 255, 100)
+    );
+    assert_eq!(canvas.get_cell(Vector2::new(3, 2)), [b' '; 2]);
+    assert_eq!(canvas.get_pixel(Vector2::new(2, 3)), Colour::zeros());
+
+    canvas.fill(Colour::new(1, 2, 3));
+    for y in 0..canvas.size().y {
+        for x in 0..canvas.size().x {
+            assert_eq!(canvas.get_pixel(Vector2::new(x, y)), Colour::new(1, 2, 3));
+        }
+    }
+}
+
+#[test]
+fn test_memory_canvas_write() {
+    let mut canvas = MemoryCanvas::new(Vector2::new(4, 2));
+    canvas.write(
+        "FPS: 60".to_string(),
+        Colour::from_element(40),
+        Vector2::new(0, 1),
+    );
+
+    assert_eq!(canvas.get_cell(Vector2::new(0, 1)), *b"FP");
+    assert_eq!(canvas.get_cell(Vector2::new(1, 1)), *b"S:");
+    assert_eq!(canvas.get_cell(Vector2::new(2, 1)), *b" 6");
+    // odd length strings are padded to whole cells
+    assert_eq!(canvas.get_cell(Vector2::new(3, 1)), *b"0 ");
+    assert_eq!(
+        canvas.get_pixel(Vector2::new(3, 1)),
+        Colour::from_element(40)
+    );
+    // the row above is untouched
+    assert_eq!(canvas.get_cell(Vector2::new(0, 0)), [b' '; 2]);
+    assert_eq!(canvas.get_pixel(Vector2::new(0, 0)), Colour::zeros());
+
+    // text running off the canvas is clipped rather than wrapped
+    canvas.write(
+        "OVERFLOWING".to_string(),
+        Colour::zeros(),
+        Vector2::new(2, 0),
+    );
+    assert_eq!(canvas.get_cell(Vector2::new(3, 0)), *b"ER");
+    assert_eq!(canvas.get_cell(Vector2::new(0, 1)), *b"FP");
+}
+
+#[test]
+fn test_memory_canvas_display() -> Result<(), std::io::Error> {
+    let mut canvas = MemoryCanvas::new(Vector2::new(2, 1));
+    canvas.put_cell(*b"hi", Colour::new(255, 0, 10), Vector2::new(1, 0));
+
+    let mut out = Vec::new();
+    canvas.display(&mut out)?;
+    assert_eq!(
+        out,
+        b"\x1B[48;2;0;0;0m  \x1B[48;2;255;0;10mhi\x1B[0m\n".to_vec()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_memory_canvas_world() {
+    use crate::graphics::{Camera, LightColour, Material, Sphere, World, WorldVector};
+
+    let mut canvas = MemoryCanvas::new(Vector2::new(16, 8));
+    // a sphere surrounding the camera is hit by every ray,
+    // with no light sources only ambient light applies
+    let world = World::new(
+        vec![Box::new(Sphere {
+            center: WorldVector::zeros(),
+            radius: 100.0,
+            material: Material {
+                colour: LightColour::x(),
+                specular: None,
+                reflective: 0.0,
+                transparency: 0.0,
+                refractive_index: 1.0,
+            },
+        })],
+        vec![],
+        Camera::new(canvas.size()),
+        LightColour::from_element(0.5),
+    );
+
+    for y in 0..canvas.size().y {
+        for x in 0..canvas.size().x {
+            let canvas_position = Vector2::new(x, y);
+            let ray = world
+                .camera
+                .primary_ray(canvas_position.cast() + Vector2::repeat(0.5), canvas.size());
+            let colour = match world.cast_ray(&ray, 1f64, 10000f64) {
+                Some(hit) => world.compute_lighting(&hit, 0),
+                None => Colour::zeros(),
+            };
+            canvas.put_pixel(colour, canvas_position);
+        }
+    }
+
+    for y in 0..canvas.size().y {
+        for x in 0..canvas.size().x {
+            assert_eq!(canvas.get_pixel(Vector2::new(x, y)), Colour::new(127, 0, 0));
+        }
+    }
+}
+
+#[test]
+fn test_presenter_differential() -> Result<(), std::io::Error> {
+    let mut presenter = Presenter::new(Vector2::new(3, 2));
+    let mut screen = vec![Glyph::default(); 6];
+
+    // the first frame is written in full, one style for the whole run
+    let mut out = Vec::new();
+    presenter.present(&screen, &mut out)?;
+    assert_eq!(
+        out,
+        b"\x1B[1;1H\x1B[39;48;2;0;0;0m   \x1B[2;1H   \x1B[0m".to_vec()
+    );
+    assert_eq!(presenter.statistics().changed, 6);
+
+    // unchanged frames write nothing
+    let mut out = Vec::new();
+    presenter.present(&screen, &mut out)?;
+    assert!(out.is_empty());
+    assert_eq!(presenter.statistics().bytes, 0);
+
+    screen[4] = Glyph {
+        char: 'x',
+        foreground: Some(Colour::new(255, 255, 255)),
+        background: Colour::new(1, 2, 3),
+    };
+    let mut out = Vec::new();
+    presenter.present(&screen, &mut out)?;
+    assert_eq!(
+        out,
+        b"\x1B[2;2H\x1B[38;2;255;255;255;48;2;1;2;3mx\x1B[0m".to_vec()
+    );
+    assert_eq!(presenter.statistics().changed, 1);
+    assert_eq!(presenter.statistics().bytes, out.len());
+
+    // when not differential every frame is written in full
+    presenter.set_differential(false);
+    let mut out = Vec::new();
+    presenter.present(&screen, &mut out)?;
+    assert_eq!(presenter.statistics().changed, 6);
+    Ok(())
+}
+
+#[test]
+fn test_half_blocks() -> Result<(), std::io::Error> {
+    let mut canvas = TerminalCanvas::with_size(HalfBlocks, Vector2::new(48, 12));
+    // two pixels per character vertically, text cells are still 2-chars wide
+    assert_eq!(canvas.size(), Vector2::new(48, 24));
+    assert_eq!(canvas.text_size(), Vector2::new(24, 12));
+
+    canvas.put_pixel(Colour::new(255, 0, 0), Vector2::new(0, 0));
+    canvas.put_pixel(Colour::new(0, 0, 255), Vector2::new(0, 1));
+    canvas.write("HUD".to_string(), Colour::new(9, 9, 9), Vector2::new(1, 11));
+
+    let mut out = Vec::new();
+    canvas.display(&mut out)?;
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.starts_with("\x1B[1;1H\x1B[38;2;255;0;0;48;2;0;0;255m▀"));
+    assert!(out.contains("\x1B[39;48;2;9;9;9mHUD "));
+    Ok(())
+}
+
+/// Resizes a canvas drawn with `mode` up and down, each size should fit
+/// the terminal and be displayed in full once, then only what changed
+fn check_resize<M: CellMode>(mode: M) -> Result<(), std::io::Error> {
+    let mut canvas = TerminalCanvas::with_size(mode, Vector2::new(48, 12));
+    canvas.set_colour_depth(ColourDepth::Ansi256);
+    for terminal in [
+        (160, 48),
+        (48, 12),
+        (47, 11),
+        (7, 3),
+        (80, 24),
+        (1, 1),
+        (0, 0),
+    ] {
+        let terminal = Vector2::new(terminal.0, terminal.1);
+        canvas.resize(terminal);
+        // tiny terminals still get a block
+        let blocks = Vector2::new(terminal.x as usize / M::COLUMNS, terminal.y as usize)
+            .sup(&Vector2::repeat(1));
+        assert_eq!(canvas.size(), blocks.component_mul(&M::PIXELS));
+        let glyphs = blocks.x * M::COLUMNS * blocks.y;
+        if terminal.x as usize >= M::COLUMNS {
+            assert!(blocks.x * M::COLUMNS <= terminal.x as usize);
+        }
+        assert_eq!(
+            canvas.text_size(),
+            Vector2::new(blocks.x * M::COLUMNS / 2, blocks.y)
+        );
+
+        canvas.put_pixel(Colour::new(1, 2, 3), canvas.size() - Vector2::repeat(1));
+        let mut out = Vec::new();
+        canvas.display(&mut out)?;
+        assert_eq!(canvas.statistics().changed, glyphs, "{terminal:?}");
+        canvas.display(&mut out)?;
+        assert_eq!(canvas.statistics().changed, 0, "{terminal:?}");
+        assert_eq!(canvas.colour_depth(), ColourDepth::Ansi256);
+    }
+
+    // frames stay written in full across resizes when asked to
+    canvas.set_differential(false);
+    canvas.resize(Vector2::new(20, 4));
+    let mut out = Vec::new();
+    canvas.display(&mut out)?;
+    canvas.display(&mut out)?;
+    assert_eq!(
+        canvas.statistics().changed,
+        20 / M::COLUMNS * M::COLUMNS * 4
+    );
+    Ok(())
+}
+
+#[test]
+fn test_resize() -> Result<(), std::io::Error> {
+    check_resize(Blocks)?;
+    check_resize(HalfBlocks)?;
+    check_resize(Quadrants)?;
+    check_resize(Braille)?;
+    check_resize(Ascii::default())
+}
+
+#[test]
+fn test_pixel_aspect() {
+    // terminal cells are twice as tall as wide
+    let size = Vector2::new(48, 12);
+    assert_eq!(TerminalCanvas::with_size(Blocks, size).pixel_aspect(), 1.0);
+    assert_eq!(
+        TerminalCanvas::with_size(HalfBlocks, size).pixel_aspect(),
+        1.0
+    );
+    assert_eq!(
+        TerminalCanvas::with_size(Quadrants, size).pixel_aspect(),
+        0.5
+    );
+    assert_eq!(TerminalCanvas::with_size(Braille, size).pixel_aspect(), 1.0);
+    assert_eq!(
+        TerminalCanvas::with_size(Ascii::default(), size).pixel_aspect(),
+        0.5
+    );
+    assert_eq!(MemoryCanvas::new(size.cast()).pixel_aspect(), 1.0);
+}
+
+#[test]
+fn test_two_colour_modes() {
+    let (red, blue) = (Colour::new(200, 0, 0), Colour::new(0, 0, 100));
+    let mut glyphs = [Glyph::default()];
+
+    Quadrants.pack(&[red, blue, blue, red], &mut glyphs);
+    assert_eq!(
+        glyphs[0],
+        Glyph {
+            char: '▚',
+            foreground: Some(red),
+            background: blue,
+        }
+    );
+
+    // a uniform block is just its background
+    Quadrants.pack(&[blue; 4], &mut glyphs);
+    assert_eq!(
+        glyphs[0],
+        Glyph {
+            char: ' ',
+            foreground: None,
+            background: blue,
+        }
+    );
+
+    // the left column and bottom-right dot
+    Braille.pack(&[red, blue, red, blue, red, blue, red, red], &mut glyphs);
+    assert_eq!(glyphs[0].char, '⣇');
+    assert_eq!(glyphs[0].foreground, Some(red));
+    assert_eq!(glyphs[0].background, blue);
+
+    // sides are the mean of their pixels
+    let dark_red = Colour::new(100, 0, 0);
+    Quadrants.pack(
+        &[red, dark_red, Colour::zeros(), Colour::zeros()],
+        &mut glyphs,
+    );
+    assert_eq!(glyphs[0].char, '▘');
+    assert_eq!(glyphs[0].background, Colour::new(33, 0, 0));
+}
+
+#[test]
+fn test_colour_depth() {
+    let depth = ColourDepth::Ansi256;
+    assert_eq!(
+        depth.nearest(Colour::new(255, 0, 0)),
+        (196, Colour::new(255, 0, 0))
+    );
+    assert_eq!(
+        depth.nearest(Colour::new(100, 140, 210)),
+        (68, Colour::new(95, 135, 215))
+    );
+    // greys use the finer greyscale ramp
+    assert_eq!(
+        depth.nearest(Colour::new(128, 128, 128)),
+        (244, Colour::from_element(128))
+    );
+    assert_eq!(depth.nearest(Colour::new(0, 0, 0)), (16, Colour::zeros()));
+
+    let depth = ColourDepth::Ansi16;
+    assert_eq!(
+        depth.nearest(Colour::new(250, 10, 10)),
+        (9, Colour::new(255, 0, 0))
+    );
+    assert_eq!(depth.nearest(Colour::new(20, 20, 30)), (0, Colour::zeros()));
+
+    let colour = Colour::new(1, 2, 3);
+    assert_eq!(ColourDepth::TrueColour.quantise(colour), colour);
+}
+
+#[test]
+fn test_colour_depth_display() -> Result<(), std::io::Error> {
+    let mut canvas = MemoryCanvas::new(Vector2::new(2, 1));
+    canvas.put_pixel(Colour::new(255, 0, 0), Vector2::new(1, 0));
+
+    canvas.set_colour_depth(ColourDepth::Ansi256);
+    let mut out = Vec::new();
+    canvas.display(&mut out)?;
+    assert_eq!(out, b"\x1B[48;5;16m  \x1B[48;5;196m  \x1B[0m\n".to_vec());
+
+    canvas.set_colour_depth(ColourDepth::Ansi16);
+    let mut out = Vec::new();
+    canvas.display(&mut out)?;
+    assert_eq!(out, b"\x1B[40m  \x1B[101m  \x1B[0m\n".to_vec());
+
+    // foreground and background are quantised alike
+    let mut presenter = Presenter::new(Vector2::new(1, 1));
+    presenter.set_colour_depth(ColourDepth::Ansi16);
+    let glyph = Glyph {
+        char: '▀',
+        foreground: Some(Colour::new(0, 200, 0)),
+        background: Colour::new(0, 0, 240),
+    };
+    let mut out = Vec::new();
+    presenter.present(&[glyph], &mut out)?;
+    assert_eq!(out, "\x1B[1;1H\x1B[32;44m▀\x1B[0m".as_bytes().to_vec());
+
+    // buffered cells keep their truecolour escape codes, with palette colours
+    let mut canvas = BufferedCanvas::<2, 1, 1>::new();
+    canvas.set_colour_depth(ColourDepth::Ansi16);
+    canvas.put_pixel(Colour::new(200, 10, 10), Vector2::new(0, 0));
+    assert!(canvas
+        .to_string()
+        .starts_with("\x1B[48;2;205;000;000m  \x1B[48;2;000;000;000m  "));
+    Ok(())
+}
+
+/// A grey gradient, black on the left to white on the right
+fn gradient(size: Vector2<usize>) -> Vec<Colour> {
+    (0..size.x * size.y)
+        .map(|i| Colour::from_element((255 * (i % size.x) / (size.x - 1)) as u8))
+        .collect()
+}
+
+/// Mean absolute difference between the column averages of two
+/// frames, how far off the dithered shading looks from afar
+fn column_error(a: &[Colour], b: &[Colour], size: Vector2<usize>) -> f64 {
+    let column_mean = |pixels: &[Colour], x: usize| {
+        (0..size.y)
+            .map(|y| pixels[x + y * size.x].x as f64)
+            .sum::<f64>()
+            / size.y as f64
+    };
+    (0..size.x)
+        .map(|x| (column_mean(a, x) - column_mean(b, x)).abs())
+        .sum::<f64>()
+        / size.x as f64
+}
+
+#[test]
+fn test_dither_gradient() {
+    let size = Vector2::new(64, 16);
+    let depth = ColourDepth::Ansi16;
+    let original = gradient(size);
+
+    let mut quantised = original.clone();
+    Dither::None.apply(&mut quantised, size, depth);
+    let banded = column_error(&original, &quantised, size);
+
+    for dither in [Dither::Bayer, Dither::FloydSteinberg] {
+        let mut dithered = original.clone();
+        dither.apply(&mut dithered, size, depth);
+        // only palette colours are output
+        assert!(dithered.iter().all(|&c| depth.quantise(c) == c));
+        // the shading is at least a third closer to the original
+        let error = column_error(&original, &dithered, size);
+        assert!(
+            error < banded * 2.0 / 3.0,
+            "{:?}: {} vs {}",
+            dither,
+            error,
+            banded
+        );
+    }
+
+    // truecolour needs no dithering
+    let mut untouched = original.clone();
+    Dither::FloydSteinberg.apply(&mut untouched, size, ColourDepth::TrueColour);
+    assert_eq!(untouched, original);
+}
+
+#[test]
+fn test_ascii() -> Result<(), std::io::Error> {
+    let ascii = Ascii::default();
+    assert_eq!(ascii.char(Colour::zeros()), ' ');
+    assert_eq!(ascii.char(Colour::from_element(255)), '@');
+    assert_eq!(ascii.char(Colour::from_element(128)), '+');
+    // green looks brighter than blue
+    assert_eq!(ascii.char(Colour::new(0, 255, 0)), '*');
+    assert_eq!(ascii.char(Colour::new(0, 0, 255)), '.');
+
+    let mut canvas = TerminalCanvas::with_size(Ascii::with_ramp(" #"), Vector2::new(48, 12));
+    canvas.set_colour_depth(ColourDepth::Monochrome);
+    assert_eq!(canvas.size(), Vector2::new(48, 12));
+    canvas.put_pixel(Colour::from_element(255), Vector2::new(1, 0));
+
+    // no colours are output, only positioning
+    let mut out = Vec::new();
+    canvas.display(&mut out)?;
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.starts_with(&format!("\x1B[1;1H #{}\x1B[2;1H", " ".repeat(46))));
+    assert!(!out.contains('m'));
+    Ok(())
+}
+
+#[test]
+fn test_export_ppm() -> Result<(), std::io::Error> {
+    let mut canvas = MemoryCanvas::new(Vector2::new(3, 2));
+    canvas.put_pixel(Colour::new(255, 128, 0), Vector2::new(0, 0));
+    canvas.put_pixel(Colour::new(1, 2, 3), Vector2::new(2, 1));
+
+    let mut image = Vec::new();
+    write_ppm(&canvas, &mut image)?;
+    assert!(image.starts_with(b"P6\n3 2\n255\n\xFF\x80\x00"));
+    assert_eq!(image.len(), 11 + 3 * 2 * 3);
+
+    // reading it back gives the same pixels
+    let golden = read_ppm(&mut image.as_slice())?;
+    assert_eq!(golden.size(), canvas.size());
+    for y in 0..2 {
+        for x in 0..3 {
+            let position = Vector2::new(x, y);
+            assert_eq!(golden.get_pixel(position), canvas.get_pixel(position));
+        }
+    }
+
+    // comments are skipped, other formats are rejected
+    let commented = read_ppm(&mut b"P6\n# golden\n1 1 255\n\x01\x02\x03".as_slice())?;
+    assert_eq!(commented.get_pixel(Vector2::zeros()), Colour::new(1, 2, 3));
+    assert!(read_ppm(&mut b"P3\n1 1\n255\n1 2 3\n".as_slice()).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_export_png() -> Result<(), std::io::Error> {
+    let mut canvas = MemoryCanvas::new(Vector2::new(2, 2));
+    canvas.put_pixel(Colour::new(255, 0, 0), Vector2::new(1, 1));
+
+    let mut image = Vec::new();
+    write_png(&canvas, &mut image)?;
+    assert!(image.starts_with(b"\x89PNG\r\n\x1A\n"));
+    // IHDR: 2x2, 8-bit RGB, with its checksum
+    assert_eq!(
+        &image[8..33],
+        b"\x00\x00\x00\x0DIHDR\x00\x00\x00\x02\x00\x00\x00\x02\x08\x02\x00\x00\x00\xFD\xD4\x9A\x73"
+    );
+    // IDAT: a single stored block of 2 filtered rows
+    assert_eq!(&image[37..41], b"IDAT");
+    assert_eq!(&image[41..48], b"\x78\x01\x01\x0E\x00\xF1\xFF");
+    assert_eq!(&image[48..62], [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 255, 0, 0]);
+    assert!(image.ends_with(b"\x00\x00\x00\x00IEND\xAE\x42\x60\x82"));
+    Ok(())
+}
+
+/// Collects each flushed frame, shared with the present thread
+#[derive(Clone, Default)]
+struct FrameLog {
+    pending: Vec<u8>,
+    frames: std::sync::Arc<std::sync::Mutex<Vec<Vec<u8>>>>,
+}
+
+impl Write for FrameLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let frame = std::mem::take(&mut self.pending);
+        self.frames.lock().unwrap().push(frame);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_swap_chain() -> Result<(), std::io::Error> {
+    for buffers in 1..=3 {
+        let log = FrameLog::default();
+        let mut chain = SwapChain::new(log.clone(), buffers);
+        for frame in 0..10u8 {
+            chain.back().extend_from_slice(&[frame; 64]);
+            chain.swap()?;
+            // buffers are reused empty
+            assert!(chain.back().is_empty());
+        }
+        chain.back().push(b'!');
+        chain.finish()?;
+
+        // every frame is written whole, in order, with the back buffer last
+        let frames = log.frames.lock().unwrap();
+        assert_eq!(frames.len(), 11);
+        for (i, frame) in frames[..10].iter().enumerate() {
+            assert_eq!(frame, &vec![i as u8; 64]);
+        }
+        assert_eq!(frames[10], b"!");
+    }
+
+    // a failed write is returned by a later swap
+    let mut chain = SwapChain::new(std::io::Cursor::new([0u8; 4]), 2);
+    chain.back().extend_from_slice(b"too long");
+    let failed = (0..2).map(|_| chain.swap()).any(|result| result.is_err());
+    assert!(failed);
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use super::{Aabb, Intersection, Material, Ray, Shape, WorldVector};
+
+/// An axis-aligned box between two opposite corners
+#[derive(Debug)]
+pub struct Cuboid {
+    pub min: WorldVector,
+    pub max: WorldVector,
+    pub material: Material,
+}
+
+impl Shape for Cuboid {
+    fn intersection(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Intersection> {
+        // the ray is inside every pair of faces, slabs, between
+        // the last it enters and the first it leaves
+        let (origin, direction) = (ray.origin(), -ray.direction());
+        let (mut near, mut far) = ((f64::NEG_INFINITY, 0), (f64::INFINITY, 0));
+        for axis in 0..3 {
+            if direction[axis].abs() < f64::EPSILON {
+                if origin[axis] < self.min[axis] || origin[axis] > self.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let t0 = (self.min[axis] - origin[axis]) / direction[axis];
+            let t1 = (self.max[axis] - origin[axis]) / direction[axis];
+            let (t0, t1) = (t0.min(t1), t0.max(t1));
+            if t0 > near.0 {
+                near = (t0, axis);
+            }
+            if t1 < far.0 {
+                far = (t1, axis);
+            }
+        }
+        if near.0 > far.0 {
+            return None;
+        }
+
+        // normals point inwards, along the ray where it enters and against it where it leaves
+        let along = |axis: usize| WorldVector::ith(axis, direction[axis].signum());
+        if near.0 > t_min && near.0 < t_max {
+            Some(Intersection {
+                t: near.0,
+                normal: along(near.1),
+            })
+        } else if far.0 > t_min && far.0 < t_max {
+            Some(Intersection {
+                t: far.0,
+                normal: -along(far.1),
+            })
+        } else {
+            None
+        }
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(Aabb {
+            min: self.min.inf(&self.max),
+            max: self.min.sup(&self.max),
+        })
+    }
+}
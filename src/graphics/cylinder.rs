@@ -0,0 +1,75 @@
+use super::{disc_bounds, Aabb, Intersection, Material, Ray, Shape, WorldVector};
+
+/// A cylinder capped at both ends, standing on `base` and
+/// running `height` along `axis`
+#[derive(Debug)]
+pub struct Cylinder {
+    /// Center of the bottom cap
+    pub base: WorldVector,
+    pub axis: WorldVector,
+    pub radius: f64,
+    pub height: f64,
+    pub material: Material,
+}
+
+impl Shape for Cylinder {
+    fn intersection(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Intersection> {
+        let axis = self.axis.normalize();
+        let (origin, direction) = (ray.origin() - self.base, -ray.direction());
+        let mut closest: Option<Intersection> = None;
+        let mut consider = |t: f64, normal: WorldVector| {
+            if t > t_min && t < closest.map_or(t_max, |closest| closest.t) {
+                closest = Some(Intersection { t, normal });
+            }
+        };
+
+        // the side, solved in the plane perpendicular to the axis
+        let perpendicular = |v: WorldVector| v - axis * v.dot(&axis);
+        let (origin_perp, direction_perp) = (perpendicular(origin), perpendicular(direction));
+        let a = direction_perp.dot(&direction_perp);
+        let b = 2.0 * origin_perp.dot(&direction_perp);
+        let c = origin_perp.dot(&origin_perp) - self.radius * self.radius;
+        let discriminant = b * b - 4.0 * a * c;
+        if a > f64::EPSILON && discriminant >= 0.0 {
+            for t in [
+                (-b - discriminant.sqrt()) / (2.0 * a),
+                (-b + discriminant.sqrt()) / (2.0 * a),
+            ] {
+                let height = (origin + direction * t).dot(&axis);
+                if (0.0..=self.height).contains(&height) {
+                    // towards the axis
+                    consider(t, -(origin_perp + direction_perp * t).normalize());
+                }
+            }
+        }
+
+        // the caps, facing into the cylinder
+        let along = direction.dot(&axis);
+        if along.abs() > f64::EPSILON {
+            for (height, normal) in [(0.0, axis), (self.height, -axis)] {
+                let t = (height - origin.dot(&axis)) / along;
+                let offset = perpendicular(origin + direction * t);
+                if offset.dot(&offset) <= self.radius * self.radius {
+                    consider(t, normal);
+                }
+            }
+        }
+        closest
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        // the caps are the furthest out
+        let top = self.base + self.axis.normalize() * self.height;
+        Some(
+            disc_bounds(self.base, self.axis, self.radius).union(&disc_bounds(
+                top,
+                self.axis,
+                self.radius,
+            )),
+        )
+    }
+}
@@ -3,7 +3,8 @@ use num_traits::Pow;
 
 use super::WorldVector;
 
-pub trait LightSource {
+/// Light sources are shared between tracing threads
+pub trait LightSource: Send + Sync {
     fn diffuse(&self, point: WorldVector, normal: WorldVector) -> LightColour;
     fn specular(
         &self,
@@ -12,28 +13,106 @@ pub trait LightSource {
         normal: WorldVector,
         specular_exponent: f64,
     ) -> LightColour;
+    /// Direction from `point` towards the light, shadow rays
+    /// follow it from the point up to `t_max` times its length
     fn light_direction(&self, point: WorldVector) -> WorldVector;
     fn t_max(&self) -> f64;
+    /// Shadow rays cast from each lit point, the light reaching
+    /// the point is scaled by the share of them that aren't blocked
+    fn samples(&self) -> usize {
+        1
+    }
+    /// Direction from `point` towards the `sample`th point spread over
+    /// the light, lights without an area have only the one point
+    fn sample_direction(&self, point: WorldVector, _sample: usize) -> WorldVector {
+        self.light_direction(point)
+    }
 }
 
 pub type LightColour = Vector3<f64>;
 
+/// How a light fades with distance, it's divided by
+/// `constant + linear * distance + quadratic * distance²`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attenuation {
+    pub constant: f64,
+    pub linear: f64,
+    pub quadratic: f64,
+}
+
 /// Omnidirectional lightsource
 #[derive(Clone, Copy)]
 pub struct PointLight {
     pub position: WorldVector,
     pub colour: LightColour,
+    pub attenuation: Attenuation,
+}
+/// A light shining from `position` in a cone around `direction`
+#[derive(Clone, Copy)]
+pub struct SpotLight {
+    pub position: WorldVector,
+    pub direction: WorldVector,
+    /// Angle from `direction` in radians, within it the light is at full strength
+    pub inner_angle: f64,
+    /// Angle from `direction` in radians, the light fades out
+    /// between the inner angle and this one
+    pub outer_angle: f64,
+    pub colour: LightColour,
+    pub attenuation: Attenuation,
 }
 #[derive(Clone, Copy)]
 pub struct DirectionalLight {
     pub direction: WorldVector,
     pub colour: LightColour,
 }
+/// A flat, rectangular light spanning `width` and `height` from
+/// `corner`, its shadows soften with distance from what casts them
+#[derive(Clone, Copy)]
+pub struct RectangleLight {
+    pub corner: WorldVector,
+    pub width: WorldVector,
+    pub height: WorldVector,
+    pub colour: LightColour,
+    /// Shadow rays cast towards the light, in a grid over it, rounded up
+    /// to fill the grid. More make smoother shadows but take longer.
+    pub samples: usize,
+}
+/// A round light, its shadows soften with distance from what casts them
+#[derive(Clone, Copy)]
+pub struct SphereLight {
+    pub center: WorldVector,
+    pub radius: f64,
+    pub colour: LightColour,
+    /// Shadow rays cast towards the light, spiralling out over it,
+    /// more make smoother shadows but take longer
+    pub samples: usize,
+}
+impl Attenuation {
+    /// The light doesn't fade
+    pub const NONE: Self = Self {
+        constant: 1.0,
+        linear: 0.0,
+        quadratic: 0.0,
+    };
+
+    /// Share of the light left after `distance`
+    pub fn at(&self, distance: f64) -> f64 {
+        1.0 / (self.constant + self.linear * distance + self.quadratic * distance * distance)
+    }
+}
+
+impl Default for Attenuation {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
 impl LightSource for PointLight {
     fn diffuse(&self, point: WorldVector, normal: WorldVector) -> LightColour {
         let direction = point - self.position;
         self.colour.cast::<f64>()
             * (normal.dot(&direction) / (normal.magnitude() * direction.magnitude()))
+            * self.attenuation.at(direction.magnitude())
     }
 
     fn specular(
@@ -47,7 +126,8 @@ impl LightSource for PointLight {
         let r = 2.0 * normal.scale(normal.dot(&direction)) - direction;
         self.colour.scale(
             (r.dot(&viewing_direction) / (r.magnitude() * viewing_direction.magnitude()))
-                .pow(specular_exponent),
+                .pow(specular_exponent)
+                * self.attenuation.at(direction.magnitude()),
         )
     }
 
@@ -81,10 +161,178 @@ impl LightSource for DirectionalLight {
     }
 
     fn light_direction(&self, _: WorldVector) -> WorldVector {
-        self.direction
+        // towards the light, against the direction it shines
+        -self.direction
     }
 
     fn t_max(&self) -> f64 {
         f64::MAX
     }
 }
+
+impl SpotLight {
+    /// Lights as a point light, before the cone is applied
+    fn point(&self) -> PointLight {
+        PointLight {
+            position: self.position,
+            colour: self.colour,
+            attenuation: self.attenuation,
+        }
+    }
+
+    /// Share of the light reaching `point` for where it is in the cone,
+    /// smoothly fading from the inner angle to the outer
+    fn cone(&self, point: WorldVector) -> f64 {
+        let angle = (point - self.position).angle(&self.direction);
+        let width = self.outer_angle - self.inner_angle;
+        if width <= 0.0 {
+            return if angle <= self.outer_angle { 1.0 } else { 0.0 };
+        }
+        let t = ((self.outer_angle - angle) / width).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl LightSource for SpotLight {
+    fn diffuse(&self, point: WorldVector, normal: WorldVector) -> LightColour {
+        self.point().diffuse(point, normal) * self.cone(point)
+    }
+
+    fn specular(
+        &self,
+        viewing_direction: WorldVector,
+        point: WorldVector,
+        normal: WorldVector,
+        specular_exponent: f64,
+    ) -> LightColour {
+        self.point()
+            .specular(viewing_direction, point, normal, specular_exponent)
+            * self.cone(point)
+    }
+
+    fn light_direction(&self, point: WorldVector) -> WorldVector {
+        self.position - point
+    }
+
+    fn t_max(&self) -> f64 {
+        1.0
+    }
+}
+
+impl RectangleLight {
+    /// Lights as a point light at the center of the rectangle
+    fn center(&self) -> PointLight {
+        PointLight {
+            position: self.corner + (self.width + self.height) / 2.0,
+            colour: self.colour,
+            attenuation: Attenuation::NONE,
+        }
+    }
+
+    /// Columns and rows of the grid shadow rays are cast to, as square
+    /// as it can be with a cell for every sample asked for
+    fn grid(&self) -> (usize, usize) {
+        let samples = self.samples.max(1);
+        let columns = (samples as f64).sqrt().ceil() as usize;
+        (columns, samples.div_ceil(columns))
+    }
+}
+
+impl LightSource for RectangleLight {
+    fn diffuse(&self, point: WorldVector, normal: WorldVector) -> LightColour {
+        self.center().diffuse(point, normal)
+    }
+
+    fn specular(
+        &self,
+        viewing_direction: WorldVector,
+        point: WorldVector,
+        normal: WorldVector,
+        specular_exponent: f64,
+    ) -> LightColour {
+        self.center()
+            .specular(viewing_direction, point, normal, specular_exponent)
+    }
+
+    fn light_direction(&self, point: WorldVector) -> WorldVector {
+        self.center().light_direction(point)
+    }
+
+    fn t_max(&self) -> f64 {
+        1.0
+    }
+
+    fn samples(&self) -> usize {
+        // every cell is sampled, so they're spread evenly over the light
+        let (columns, rows) = self.grid();
+        columns * rows
+    }
+
+    fn sample_direction(&self, point: WorldVector, sample: usize) -> WorldVector {
+        // the middle of a cell of a grid over the rectangle
+        let (columns, rows) = self.grid();
+        let across = (sample % columns) as f64 + 0.5;
+        let down = (sample / columns) as f64 + 0.5;
+        self.corner + self.width * (across / columns as f64) + self.height * (down / rows as f64)
+            - point
+    }
+}
+
+impl SphereLight {
+    /// Lights as a point light at the center of the sphere
+    fn center(&self) -> PointLight {
+        PointLight {
+            position: self.center,
+            colour: self.colour,
+            attenuation: Attenuation::NONE,
+        }
+    }
+}
+
+impl LightSource for SphereLight {
+    fn diffuse(&self, point: WorldVector, normal: WorldVector) -> LightColour {
+        self.center().diffuse(point, normal)
+    }
+
+    fn specular(
+        &self,
+        viewing_direction: WorldVector,
+        point: WorldVector,
+        normal: WorldVector,
+        specular_exponent: f64,
+    ) -> LightColour {
+        self.center()
+            .specular(viewing_direction, point, normal, specular_exponent)
+    }
+
+    fn light_direction(&self, point: WorldVector) -> WorldVector {
+        self.center - point
+    }
+
+    fn t_max(&self) -> f64 {
+        1.0
+    }
+
+    fn samples(&self) -> usize {
+        self.samples.max(1)
+    }
+
+    fn sample_direction(&self, point: WorldVector, sample: usize) -> WorldVector {
+        // the sphere looks like a disc from the point, samples spiral out
+        // over it evenly from its center, turning by the golden angle
+        let towards = self.center - point;
+        if towards.norm() <= self.radius {
+            return towards;
+        }
+        let across = if towards.x.abs() < 0.9 * towards.norm() {
+            WorldVector::x()
+        } else {
+            WorldVector::y()
+        };
+        let u = towards.cross(&across).normalize();
+        let v = towards.normalize().cross(&u);
+        let radius = self.radius * (sample as f64 / self.samples() as f64).sqrt();
+        let angle = sample as f64 * std::f64::consts::PI * (3.0 - 5f64.sqrt());
+        towards + (u * angle.cos() + v * angle.sin()) * radius
+    }
+}
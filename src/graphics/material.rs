@@ -6,6 +6,15 @@ pub struct Material {
     /// Specular exponent
     pub specular: Option<f64>,
     /// Colour of the material
-    pub colour: LightColour
+    pub colour: LightColour,
+    /// How much of the surface's colour is reflected from other
+    /// objects, 0 is matte and 1 a perfect mirror
+    pub reflective: f64,
+    /// How much light passes through the surface, 0 is opaque
+    /// and 1 is clear, light passing through is tinted by `colour`
+    pub transparency: f64,
+    /// Refractive index of the material's inside, glass is about 1.5.
+    /// Objects are assumed to be surrounded by air, of index 1.
+    pub refractive_index: f64,
 }
 
@@ -0,0 +1,98 @@
+use super::{barycentric, Aabb, Bvh, Intersection, Material, Ray, Shape, WorldVector};
+
+/// A triangle of a mesh, with a normal at each vertex
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshTriangle {
+    /// Vertices, counter-clockwise seen from outside the mesh
+    pub vertices: [WorldVector; 3],
+    /// Normals pointing out of the mesh at each vertex, the
+    /// triangle is drawn flat without them
+    pub normals: Option<[WorldVector; 3]>,
+}
+
+/// A solid made of triangles sharing a material. Normals are
+/// interpolated across each triangle so curved models look smooth.
+#[derive(Debug)]
+pub struct Mesh {
+    triangles: Vec<MeshTriangle>,
+    pub material: Material,
+    /// Finds which triangles a ray might hit
+    bvh: Bvh,
+}
+
+impl Mesh {
+    pub fn new(triangles: Vec<MeshTriangle>, material: Material) -> Self {
+        let bvh = Bvh::build(&Self::triangle_bounds(&triangles));
+        Self {
+            triangles,
+            material,
+            bvh,
+        }
+    }
+
+    pub fn triangles(&self) -> &[MeshTriangle] {
+        &self.triangles
+    }
+
+    /// Scales the mesh about the origin, then moves it by `offset`
+    pub fn place(&mut self, scale: f64, offset: WorldVector) {
+        for triangle in &mut self.triangles {
+            for vertex in &mut triangle.vertices {
+                *vertex = *vertex * scale + offset;
+            }
+        }
+        // the triangles keep their places relative to each other
+        self.bvh.refit(&Self::triangle_bounds(&self.triangles));
+    }
+
+    fn triangle_bounds(triangles: &[MeshTriangle]) -> Vec<Option<Aabb>> {
+        triangles
+            .iter()
+            .map(|triangle| Some(Aabb::around(triangle.vertices)))
+            .collect()
+    }
+}
+
+impl MeshTriangle {
+    /// Normal at the hit with barycentric coordinates `u` and `v`,
+    /// pointing into the mesh
+    fn normal(&self, u: f64, v: f64) -> WorldVector {
+        let outwards = match self.normals {
+            Some([a, b, c]) => a * (1.0 - u - v) + b * u + c * v,
+            None => {
+                let [a, b, c] = self.vertices;
+                (b - a).cross(&(c - a))
+            }
+        };
+        -outwards.normalize()
+    }
+}
+
+impl Shape for Mesh {
+    fn intersection(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Intersection> {
+        let (index, (t, u, v)) = self.bvh.closest(ray, t_min, t_max, |i, t_max| {
+            barycentric(&self.triangles[i].vertices, ray)
+                .filter(|&(t, ..)| t > t_min && t < t_max)
+                .map(|hit| (hit.0, hit))
+        })?;
+        // only the nearest hit's normal is interpolated
+        Some(Intersection {
+            t,
+            normal: self.triangles[index].normal(u, v),
+        })
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(
+            self.triangles
+                .iter()
+                .fold(Aabb::empty(), |bounds, triangle| {
+                    bounds.union(&Aabb::around(triangle.vertices))
+                }),
+        )
+    }
+}
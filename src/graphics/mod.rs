@@ -1,19 +1,40 @@
+mod bvh;
 mod canvas;
+mod cuboid;
+mod cylinder;
+mod mesh;
+mod obj;
+mod plane;
+mod shape;
 mod sphere;
+mod triangle;
 mod viewport;
 mod world;
 mod light; 
 mod material;
 mod camera;
+mod renderer;
+mod tracer;
 mod util;
+mod test;
 use nalgebra::{Vector2, Vector3};
 
+pub use bvh::*;
 pub use canvas::*;
+pub use cuboid::*;
+pub use cylinder::*;
+pub use mesh::*;
+pub use obj::*;
+pub use plane::*;
+pub use shape::*;
 pub use sphere::*;
+pub use triangle::*;
 pub use world::*;
 pub use light::*;
 pub use material::*;
 pub use camera::*;
+pub use renderer::*;
+pub use tracer::*;
 
 pub type CanvasVector = Vector2<usize>;
 pub type WorldVector = Vector3<f64>;
@@ -33,6 +54,10 @@ impl Ray {
         self.from - t * self.direction()
     }
     #[inline]
+    pub fn origin(&self) -> WorldVector {
+        self.from
+    }
+    #[inline]
     pub fn direction(&self) -> WorldVector {
         self.from - self.to
     }
@@ -0,0 +1,196 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+use super::{LightColour, Material, Mesh, MeshTriangle, WorldVector};
+
+/// Opens a material library by the name a model gives it
+pub type OpenLibrary<'a> = dyn FnMut(&str) -> Result<Box<dyn BufRead>, io::Error> + 'a;
+
+/// Material of faces that don't name one
+fn default_material() -> Material {
+    Material {
+        specular: None,
+        colour: LightColour::from_element(0.8),
+        reflective: 0.0,
+        transparency: 0.0,
+        refractive_index: 1.0,
+    }
+}
+
+fn invalid(line: usize, reason: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {line}: {reason}"))
+}
+
+/// Parses the numbers following a keyword, at least `count` are expected
+fn numbers<'a>(
+    values: impl Iterator<Item = &'a str>,
+    count: usize,
+    line: usize,
+) -> Result<Vec<f64>, io::Error> {
+    let numbers = values
+        .map(|value| {
+            value
+                .parse::<f64>()
+                .map_err(|_| invalid(line, format!("`{value}` isn't a number")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if numbers.len() < count {
+        return Err(invalid(line, format!("expected {count} numbers")));
+    }
+    Ok(numbers)
+}
+
+/// Resolves a 1-based, or negative and relative to the end,
+/// OBJ index into one of `len` elements
+fn index(value: &str, len: usize, line: usize) -> Result<usize, io::Error> {
+    let index = value
+        .parse::<isize>()
+        .map_err(|_| invalid(line, format!("`{value}` isn't an index")))?;
+    let resolved = if index < 0 {
+        len as isize + index
+    } else {
+        index - 1
+    };
+    if resolved < 0 || resolved as usize >= len {
+        return Err(invalid(line, format!("index {index} is out of range")));
+    }
+    Ok(resolved as usize)
+}
+
+/// Reads a Wavefront OBJ model, giving a mesh per material its faces
+/// use. Polygons are split into triangles, texture coordinates and
+/// grouping are ignored. `open` opens the material libraries the model
+/// names, the model is read line by line so errors give the line.
+/// Libraries that can't be read and materials they don't define
+/// leave the faces in the default material.
+pub fn read_obj(input: &mut dyn BufRead, open: &mut OpenLibrary) -> Result<Vec<Mesh>, io::Error> {
+    let mut vertices = Vec::new();
+    let mut normals = Vec::new();
+    let mut materials = HashMap::new();
+    // triangles by material name, in the order they're first used
+    let mut meshes: Vec<(String, Vec<MeshTriangle>)> = Vec::new();
+    let mut current = None;
+
+    for (i, text) in input.lines().enumerate() {
+        let (text, line) = (text?, i + 1);
+        let mut values = text.split('#').next().unwrap_or("").split_whitespace();
+        match values.next() {
+            Some("v") => {
+                let v = numbers(values, 3, line)?;
+                vertices.push(WorldVector::new(v[0], v[1], v[2]));
+            }
+            Some("vn") => {
+                let n = numbers(values, 3, line)?;
+                normals.push(WorldVector::new(n[0], n[1], n[2]));
+            }
+            Some("f") => {
+                // each corner is `v`, `v/vt`, `v//vn` or `v/vt/vn`
+                let mut corners = Vec::new();
+                for corner in values {
+                    let mut parts = corner.split('/');
+                    let vertex = index(parts.next().unwrap_or(""), vertices.len(), line)?;
+                    let normal = match parts.nth(1) {
+                        Some(normal) if !normal.is_empty() => {
+                            Some(normals[index(normal, normals.len(), line)?])
+                        }
+                        _ => None,
+                    };
+                    corners.push((vertices[vertex], normal));
+                }
+                if corners.len() < 3 {
+                    return Err(invalid(line, "a face needs at least 3 vertices"));
+                }
+
+                let name: &String = current.get_or_insert_with(String::new);
+                let triangles = match meshes.iter().position(|(used, _)| used == name) {
+                    Some(position) => &mut meshes[position].1,
+                    None => {
+                        meshes.push((name.clone(), Vec::new()));
+                        &mut meshes.last_mut().unwrap().1
+                    }
+                };
+                // fanned out from the first corner
+                for pair in corners[1..].windows(2) {
+                    let [a, b, c] = [corners[0], pair[0], pair[1]];
+                    triangles.push(MeshTriangle {
+                        vertices: [a.0, b.0, c.0],
+                        normals: a.1.zip(b.1).zip(c.1).map(|((a, b), c)| [a, b, c]),
+                    });
+                }
+            }
+            Some("usemtl") => {
+                current = Some(values.next().unwrap_or("").to_string());
+            }
+            Some("mtllib") => {
+                for name in values {
+                    // the model is still drawn without its materials,
+                    // but a library that was read must be well formed
+                    match open(name).and_then(|mut library| read_mtl(&mut library)) {
+                        Ok(library) => materials.extend(library),
+                        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                            return Err(invalid(line, format!("{name}: {e}")));
+                        }
+                        Err(_) => {}
+                    }
+                }
+            }
+            // texture coordinates, groups, smoothing and the rest
+            _ => {}
+        }
+    }
+    Ok(meshes
+        .into_iter()
+        .map(|(name, triangles)| {
+            let material = materials.get(&name).cloned();
+            Mesh::new(triangles, material.unwrap_or_else(default_material))
+        })
+        .collect())
+}
+
+/// Reads a Wavefront MTL material library, materials by name. Only the
+/// diffuse colour, `Kd`, and specular exponent, `Ns`, are used.
+pub fn read_mtl(input: &mut dyn BufRead) -> Result<HashMap<String, Material>, io::Error> {
+    let mut materials = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for (i, text) in input.lines().enumerate() {
+        let (text, line) = (text?, i + 1);
+        let mut values = text.split('#').next().unwrap_or("").split_whitespace();
+        let keyword = values.next();
+        if let Some("newmtl") = keyword {
+            let name = values.next().unwrap_or("").to_string();
+            materials.insert(name.clone(), default_material());
+            current = Some(name);
+            continue;
+        }
+        let Some(keyword @ ("Kd" | "Ns")) = keyword else {
+            continue;
+        };
+        let material = current
+            .as_ref()
+            .and_then(|name| materials.get_mut(name))
+            .ok_or_else(|| invalid(line, format!("`{keyword}` before `newmtl`")))?;
+        if keyword == "Kd" {
+            let kd = numbers(values, 3, line)?;
+            material.colour = LightColour::new(kd[0], kd[1], kd[2]);
+        } else {
+            // an exponent of 0 is used for no highlights
+            let exponent = numbers(values, 1, line)?[0];
+            material.specular = (exponent > 0.0).then_some(exponent);
+        }
+    }
+    Ok(materials)
+}
+
+/// Loads a model from an OBJ file, its material
+/// libraries are looked for beside it
+pub fn load_obj(path: &Path) -> Result<Vec<Mesh>, io::Error> {
+    let directory = path.parent().unwrap_or(Path::new(""));
+    read_obj(&mut BufReader::new(File::open(path)?), &mut |name| {
+        Ok(Box::new(BufReader::new(File::open(directory.join(name))?)))
+    })
+}
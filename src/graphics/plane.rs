@@ -0,0 +1,79 @@
+use super::{facing, Aabb, Intersection, Material, Ray, Shape, WorldVector};
+
+/// An infinite plane through `point`, seen from both sides
+#[derive(Debug)]
+pub struct Plane {
+    pub point: WorldVector,
+    pub normal: WorldVector,
+    pub material: Material,
+}
+
+/// A flat, round disc seen from both sides
+#[derive(Debug)]
+pub struct Disc {
+    pub center: WorldVector,
+    pub normal: WorldVector,
+    pub radius: f64,
+    pub material: Material,
+}
+
+/// Returns where `ray` crosses the plane through `point`,
+/// `None` if the ray runs parallel to it
+#[inline]
+fn cross_plane(ray: &Ray, point: WorldVector, normal: WorldVector) -> Option<f64> {
+    // `Ray::direction` points back along the ray
+    let denominator = normal.dot(&-ray.direction());
+    if denominator.abs() < f64::EPSILON {
+        return None;
+    }
+    Some((point - ray.origin()).dot(&normal) / denominator)
+}
+
+impl Shape for Plane {
+    fn intersection(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Intersection> {
+        let t = cross_plane(ray, self.point, self.normal)?;
+        (t > t_min && t < t_max).then(|| Intersection {
+            t,
+            normal: facing(self.normal.normalize(), -ray.direction()),
+        })
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+impl Shape for Disc {
+    fn intersection(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Intersection> {
+        let t = cross_plane(ray, self.center, self.normal)?;
+        let offset = ray.point(t) - self.center;
+        (t > t_min && t < t_max && offset.dot(&offset) <= self.radius * self.radius).then(|| {
+            Intersection {
+                t,
+                normal: facing(self.normal.normalize(), -ray.direction()),
+            }
+        })
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(disc_bounds(self.center, self.normal, self.radius))
+    }
+}
+
+/// Box around a disc, it reaches less far along the axes its normal leans along
+pub(super) fn disc_bounds(center: WorldVector, normal: WorldVector, radius: f64) -> Aabb {
+    let normal = normal.normalize();
+    let reach = normal.map(|n| radius * (1.0 - n * n).max(0.0).sqrt());
+    Aabb {
+        min: center - reach,
+        max: center + reach,
+    }
+}
@@ -0,0 +1,11 @@
+use super::{Canvas, World};
+
+/// Draws frames of a world to a canvas, implementations are rendering
+/// strategies that can be swapped to compare them
+pub trait Renderer {
+    /// Renders a frame of `world` the size of `canvas` to it
+    fn render(&mut self, world: &World, canvas: &mut dyn Canvas);
+
+    /// Describes the renderer and its settings, for the HUD and benchmarks
+    fn name(&self) -> String;
+}
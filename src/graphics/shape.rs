@@ -0,0 +1,34 @@
+use super::{Aabb, Material, Ray, WorldVector};
+
+/// Where a ray hits a shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Intersection {
+    /// How far along the ray the hit is, in multiples of its direction
+    pub t: f64,
+    /// Normal of the surface at the hit, pointing into the shape.
+    /// Flat shapes have no inside, their normal points away from the
+    /// side the ray came from.
+    pub normal: WorldVector,
+}
+
+/// An object in the world that rays can hit,
+/// shapes are shared between tracing threads
+pub trait Shape: Send + Sync {
+    /// Returns the nearest intersection of `ray` between `t_min` and `t_max`
+    fn intersection(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Intersection>;
+
+    fn material(&self) -> &Material;
+
+    /// Box the shape fits in, `None` for endless shapes such as planes
+    fn bounds(&self) -> Option<Aabb>;
+}
+
+/// Turns `normal` to point along `direction`, for shapes without an inside
+#[inline]
+pub(super) fn facing(normal: WorldVector, direction: WorldVector) -> WorldVector {
+    if normal.dot(&direction) < 0.0 {
+        -normal
+    } else {
+        normal
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Material, Ray, WorldVector};
+use super::{Aabb, Intersection, Material, Ray, Shape, WorldVector};
 #[derive(Debug)]
 pub struct Sphere {
     pub center: WorldVector,
@@ -14,11 +14,17 @@ impl Sphere {
         d.dot(&d) < self.radius * self.radius
     }
 
+    /// Returns how far along `ray` it crosses the sphere's surface, in
+    /// multiples of its direction, the further first. A ray starting
+    /// inside the sphere crosses it behind its origin too.
     pub fn intersect(&self, ray: &Ray) -> Option<(f64, f64)> {
-        let dir = ray.direction();
+        // solves |origin + t * direction - center| = radius,
+        // `Ray::direction` points back along the ray
+        let dir = -ray.direction();
+        let offset = ray.origin() - self.center;
         let a = dir.dot(&dir);
-        let b = 2f64 * self.center.dot(&dir);
-        let c = self.center.dot(&self.center) - self.radius*self.radius;
+        let b = 2f64 * offset.dot(&dir);
+        let c = offset.dot(&offset) - self.radius * self.radius;
         let discriminant = b * b - 4f64 * a * c;
         if discriminant < 0f64 {
             return None;
@@ -29,3 +35,29 @@ impl Sphere {
         ))
     }
 }
+
+impl Shape for Sphere {
+    fn intersection(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Intersection> {
+        let (t1, t2) = self.intersect(ray)?;
+        let t = [t1, t2]
+            .into_iter()
+            .filter(|&t| t > t_min && t < t_max)
+            .min_by(f64::total_cmp)?;
+        Some(Intersection {
+            t,
+            normal: (self.center - ray.point(t)).normalize(),
+        })
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        let radius = WorldVector::repeat(self.radius);
+        Some(Aabb {
+            min: self.center - radius,
+            max: self.center + radius,
+        })
+    }
+}
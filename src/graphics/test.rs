@@ -0,0 +1,826 @@
+#![cfg(test)]
+use super::*;
+
+/// Spheres lit by a point and a directional light, with shadows
+fn scene(canvas_size: CanvasVector) -> World {
+    let sphere = |center: WorldVector, colour: LightColour| Sphere {
+        center,
+        radius: 5.0,
+        material: Material {
+            colour,
+            specular: Some(100.0),
+            reflective: 0.5,
+            transparency: 0.0,
+            refractive_index: 1.0,
+        },
+    };
+    World::new(
+        vec![
+            Box::new(sphere(WorldVector::new(0.0, 6.0, 10.0), LightColour::x())),
+            Box::new(sphere(WorldVector::new(3.0, -2.0, 14.0), LightColour::y())),
+            Box::new(sphere(WorldVector::new(-8.0, 0.0, 12.0), LightColour::z())),
+        ],
+        vec![
+            Box::new(PointLight {
+                position: WorldVector::zeros(),
+                colour: LightColour::from_element(0.5),
+                attenuation: Attenuation::NONE,
+            }),
+            Box::new(DirectionalLight {
+                direction: WorldVector::new(-1.0, -1.0, 1.0).normalize(),
+                colour: LightColour::from_element(1.0),
+            }),
+        ],
+        Camera::new(canvas_size),
+        LightColour::from_element(0.2),
+    )
+}
+
+#[test]
+fn test_tracer_threads() {
+    let size = CanvasVector::new(24, 13);
+    let world = scene(size);
+    let serial = (0..size.y)
+        .flat_map(|y| (0..size.x).map(move |x| CanvasVector::new(x, y)))
+        .map(|position| trace_pixel(&world, position, size, Tracer::DEFAULT_REFLECTION_DEPTH))
+        .collect::<Vec<_>>();
+    assert!(serial.iter().any(|&pixel| pixel != Colour::zeros()));
+
+    // more threads than rows is fine too
+    for threads in [1, 2, 5, 32] {
+        let mut tracer = Tracer::new(threads);
+        assert_eq!(tracer.threads(), threads);
+        assert_eq!(tracer.trace(&world, size), serial.as_slice());
+        // the workers are kept for later frames
+        assert_eq!(tracer.trace(&world, size), serial.as_slice());
+
+        let mut canvas = MemoryCanvas::new(size);
+        let renderer: &mut dyn Renderer = &mut tracer;
+        renderer.render(&world, &mut canvas);
+        assert_eq!(
+            canvas.get_pixel(CanvasVector::new(23, 12)),
+            serial[size.x * size.y - 1]
+        );
+    }
+}
+
+/// A sphere of `colour` and `reflective`ness, lit only by ambient light
+fn ambient_sphere(center: WorldVector, colour: LightColour, reflective: f64) -> Sphere {
+    Sphere {
+        center,
+        radius: 1.0,
+        material: Material {
+            colour,
+            specular: None,
+            reflective,
+            transparency: 0.0,
+            refractive_index: 1.0,
+        },
+    }
+}
+
+#[test]
+fn test_reflections() {
+    let mirror = |reflective| {
+        ambient_sphere(
+            WorldVector::new(0.0, 0.0, 10.0),
+            LightColour::x(),
+            reflective,
+        )
+    };
+    let mut world = World::new(
+        vec![Box::new(mirror(0.5))],
+        vec![],
+        Camera::new(CanvasVector::new(8, 8)),
+        LightColour::from_element(0.5),
+    );
+    // straight at the mirror, its reflection points back past the camera
+    let ray = Ray::new(WorldVector::zeros(), WorldVector::z());
+    let hit = world.cast_ray(&ray, 1.0, 10000.0).unwrap();
+    assert!((reflect(hit.direction, hit.normal) + WorldVector::z()).norm() < 1e-9);
+
+    // without reflections only the mirror's own colour is seen,
+    // reflecting nothing blends in black
+    assert_eq!(world.compute_lighting(&hit, 0), Colour::new(127, 0, 0));
+    assert_eq!(world.compute_lighting(&hit, 1), Colour::new(63, 0, 0));
+
+    // a sphere behind the camera is seen in the mirror
+    world.add_shape(Box::new(ambient_sphere(
+        WorldVector::new(0.0, 0.0, -10.0),
+        LightColour::y(),
+        0.0,
+    )));
+    assert_eq!(world.compute_lighting(&hit, 1), Colour::new(63, 63, 0));
+    let traced = world.trace(&ray, 1.0, 10000.0, 1);
+    assert!((traced - LightColour::new(0.25, 0.25, 0.0)).norm() < 1e-9);
+
+    // a perfect mirror is only its reflection
+    world.set_shape(0, Box::new(mirror(1.0)));
+    let hit = world.cast_ray(&ray, 1.0, 10000.0).unwrap();
+    assert_eq!(world.compute_lighting(&hit, 1), Colour::new(0, 127, 0));
+}
+
+#[test]
+fn test_refraction() {
+    let normal = -WorldVector::z();
+    // straight on, light passes straight through
+    let straight = refract(WorldVector::z(), normal, 1.0 / 1.5).unwrap();
+    assert!((straight - WorldVector::z()).norm() < 1e-9);
+
+    // Snell's law, into glass at 45 degrees
+    let incident = WorldVector::new(1.0, 0.0, 1.0);
+    let refracted = refract(incident, normal, 1.0 / 1.5).unwrap();
+    let sin = |v: WorldVector| v.normalize().cross(&normal).norm();
+    assert!((sin(incident) / sin(refracted) - 1.5).abs() < 1e-9);
+    assert!(refracted.z > 0.0);
+
+    // out of glass past the critical angle of about 42 degrees
+    let steep = WorldVector::new(60f64.to_radians().tan(), 0.0, 1.0);
+    assert!(refract(steep, normal, 1.5).is_none());
+    assert!(refract(incident * 0.5 + WorldVector::z(), normal, 1.5).is_some());
+
+    // 4% of light is reflected by glass straight on, all of it grazing
+    // or when totally internally reflected
+    assert!((schlick(1.0, 1.0, 1.5) - 0.04).abs() < 1e-9);
+    assert!((schlick(0.0, 1.0, 1.5) - 1.0).abs() < 1e-9);
+    assert_eq!(schlick(60f64.to_radians().cos(), 1.5, 1.0), 1.0);
+    assert_eq!(schlick(1.0, 1.0, 1.0), 0.0);
+}
+
+#[test]
+fn test_sphere_intersect() {
+    let sphere = ambient_sphere(WorldVector::new(5.0, 5.0, 10.0), LightColour::x(), 0.0);
+    let unit = Sphere {
+        radius: 2.0,
+        ..sphere
+    };
+    let at = |x, y, z| WorldVector::new(x, y, z);
+    // ray from, ray to, where it crosses, the further first
+    let cases = [
+        // from the origin, straight at it
+        (
+            at(0.0, 0.0, 0.0),
+            at(0.5, 0.5, 1.0),
+            Some((10.0 + 2.0 / 1.5f64.sqrt(), 10.0 - 2.0 / 1.5f64.sqrt())),
+        ),
+        // from beside the origin
+        (at(5.0, 5.0, 0.0), at(5.0, 5.0, 1.0), Some((12.0, 8.0))),
+        (at(5.0, 5.0, 0.0), at(5.0, 5.0, 2.0), Some((6.0, 4.0))),
+        (at(10.0, 5.0, 0.0), at(10.0, 5.0, 1.0), None),
+        // from inside, it's crossed behind and ahead
+        (at(5.0, 5.0, 10.0), at(5.0, 5.0, 11.0), Some((2.0, -2.0))),
+        (at(5.0, 5.0, 11.0), at(5.0, 5.0, 10.0), Some((3.0, -1.0))),
+        // grazing the edge
+        (at(7.0, 5.0, 0.0), at(7.0, 5.0, 1.0), Some((10.0, 10.0))),
+        (at(7.0, 5.0, 0.0), at(7.0, 5.0, -1.0), Some((-10.0, -10.0))),
+        // pointing away
+        (at(5.0, 5.0, 20.0), at(5.0, 5.0, 21.0), Some((-8.0, -12.0))),
+    ];
+    for (from, to, expected) in cases {
+        let crossed = unit.intersect(&Ray::new(from, to));
+        match (crossed, expected) {
+            (Some(crossed), Some(expected)) => {
+                assert!((crossed.0 - expected.0).abs() < 1e-9, "{from} {crossed:?}");
+                assert!((crossed.1 - expected.1).abs() < 1e-9, "{from} {crossed:?}");
+            }
+            _ => assert_eq!(crossed, expected, "{from}"),
+        }
+    }
+
+    // as a shape only hits ahead count, normals face in from inside too
+    let inside = unit
+        .intersection(
+            &Ray::new(at(5.0, 5.0, 11.0), at(5.0, 5.0, 10.0)),
+            0.001,
+            10000.0,
+        )
+        .unwrap();
+    assert!((inside.t - 3.0).abs() < 1e-9);
+    assert!((inside.normal - WorldVector::z()).norm() < 1e-9);
+    let away = Ray::new(at(5.0, 5.0, 20.0), at(5.0, 5.0, 21.0));
+    assert!(unit.intersection(&away, 0.001, 10000.0).is_none());
+}
+
+#[test]
+fn test_shadows() {
+    let mut lit = ambient_sphere(WorldVector::new(0.0, 0.0, 10.0), LightColour::x(), 0.0);
+    lit.material.specular = Some(10.0);
+    let hit = Hit {
+        point: WorldVector::new(0.0, 0.0, 9.0),
+        normal: WorldVector::z(),
+        direction: WorldVector::z(),
+        material: lit.material.clone(),
+    };
+    let light = || -> Vec<Box<dyn LightSource>> {
+        vec![Box::new(PointLight {
+            position: WorldVector::zeros(),
+            colour: LightColour::from_element(0.5),
+            attenuation: Attenuation::NONE,
+        })]
+    };
+    let camera = || Camera::new(CanvasVector::new(8, 8));
+
+    // shadow rays start at the hit, a sphere beside them doesn't block the light
+    let beside = ambient_sphere(WorldVector::new(3.0, 0.0, 4.5), LightColour::y(), 0.0);
+    let world = World::new(
+        vec![Box::new(beside)],
+        light(),
+        camera(),
+        LightColour::zeros(),
+    );
+    assert_eq!(
+        world.get_lighting(&hit).specular,
+        LightColour::from_element(0.5)
+    );
+
+    // one between the hit and the light casts a shadow, one past the light doesn't
+    let between = ambient_sphere(WorldVector::new(0.0, 0.0, 4.5), LightColour::y(), 0.0);
+    let world = World::new(
+        vec![Box::new(between)],
+        light(),
+        camera(),
+        LightColour::zeros(),
+    );
+    assert_eq!(world.get_lighting(&hit).specular, LightColour::zeros());
+    let past = ambient_sphere(WorldVector::new(0.0, 0.0, -3.0), LightColour::y(), 0.0);
+    let world = World::new(
+        vec![Box::new(past)],
+        light(),
+        camera(),
+        LightColour::zeros(),
+    );
+    assert_eq!(
+        world.get_lighting(&hit).specular,
+        LightColour::from_element(0.5)
+    );
+}
+
+#[test]
+fn test_glass_sphere() {
+    // straight through a glass ball to a green one behind it
+    let mut glass = ambient_sphere(
+        WorldVector::new(0.0, 0.0, 10.0),
+        LightColour::repeat(1.0),
+        0.0,
+    );
+    glass.radius = 2.0;
+    glass.material.transparency = 1.0;
+    glass.material.refractive_index = 1.5;
+    let behind = ambient_sphere(WorldVector::new(0.0, 0.0, 20.0), LightColour::y(), 0.0);
+    let world = World::new(
+        vec![Box::new(glass), Box::new(behind)],
+        vec![],
+        Camera::new(CanvasVector::new(8, 8)),
+        LightColour::from_element(1.0),
+    );
+    let ray = Ray::new(WorldVector::zeros(), WorldVector::z());
+    // the ray leaves the ball from inside it, losing 4% to reflection
+    // each time it crosses its surface, a little of the ball's own
+    // colour is reflected back and forth inside it
+    let seen = world.trace(&ray, 1.0, 10000.0, 3);
+    assert!((seen.y - 0.96 * 0.96).abs() < 0.01, "{seen}");
+    assert!(seen.x < 0.01 && seen.z < 0.01, "{seen}");
+    // without following refracted rays only its own colour is seen
+    assert_eq!(world.trace(&ray, 1.0, 10000.0, 0), LightColour::repeat(1.0));
+}
+
+#[test]
+fn test_shapes() {
+    let material = || Material {
+        colour: LightColour::x(),
+        specular: None,
+        reflective: 0.0,
+        transparency: 0.0,
+        refractive_index: 1.0,
+    };
+    let hit = |shape: &dyn Shape, from: WorldVector, to: WorldVector| {
+        shape.intersection(&Ray::new(from, to), 0.0, f64::INFINITY)
+    };
+    let close = |a: WorldVector, b: WorldVector| (a - b).norm() < 1e-9;
+
+    // a floor seen from above and below, normals face along the ray
+    let floor = Plane {
+        point: WorldVector::new(0.0, -2.0, 0.0),
+        normal: WorldVector::y(),
+        material: material(),
+    };
+    let down = hit(&floor, WorldVector::zeros(), -WorldVector::y()).unwrap();
+    assert!((down.t - 2.0).abs() < 1e-9 && close(down.normal, -WorldVector::y()));
+    let up = hit(&floor, -4.0 * WorldVector::y(), -5.0 * WorldVector::y());
+    assert!(up.is_none());
+    let up = hit(&floor, -4.0 * WorldVector::y(), -3.0 * WorldVector::y()).unwrap();
+    assert!((up.t - 2.0).abs() < 1e-9 && close(up.normal, WorldVector::y()));
+    assert!(hit(&floor, WorldVector::zeros(), WorldVector::x()).is_none());
+
+    let disc = Disc {
+        center: WorldVector::new(0.0, 0.0, 5.0),
+        normal: WorldVector::z(),
+        radius: 1.0,
+        material: material(),
+    };
+    assert!(hit(&disc, WorldVector::zeros(), WorldVector::z()).is_some());
+    assert!(hit(&disc, WorldVector::zeros(), WorldVector::new(0.3, 0.0, 1.0)).is_none());
+
+    let triangle = Triangle {
+        vertices: [
+            WorldVector::new(-1.0, -1.0, 5.0),
+            WorldVector::new(1.0, -1.0, 5.0),
+            WorldVector::new(0.0, 1.0, 5.0),
+        ],
+        material: material(),
+    };
+    let front = hit(&triangle, WorldVector::zeros(), WorldVector::z()).unwrap();
+    assert!((front.t - 5.0).abs() < 1e-9 && close(front.normal, WorldVector::z()));
+    assert!(hit(
+        &triangle,
+        WorldVector::zeros(),
+        WorldVector::new(0.2, 0.0, 1.0)
+    )
+    .is_none());
+
+    // solid shapes' normals face inwards, also when hit from inside
+    let cuboid = Cuboid {
+        min: WorldVector::new(-1.0, -1.0, 4.0),
+        max: WorldVector::new(1.0, 1.0, 6.0),
+        material: material(),
+    };
+    let outside = hit(&cuboid, WorldVector::zeros(), WorldVector::z()).unwrap();
+    assert!((outside.t - 4.0).abs() < 1e-9 && close(outside.normal, WorldVector::z()));
+    let side = hit(
+        &cuboid,
+        WorldVector::new(-5.0, 0.0, 5.0),
+        WorldVector::new(-4.0, 0.0, 5.0),
+    );
+    assert!(close(side.unwrap().normal, WorldVector::x()));
+    let inside = hit(&cuboid, 5.0 * WorldVector::z(), 6.0 * WorldVector::z()).unwrap();
+    assert!((inside.t - 1.0).abs() < 1e-9 && close(inside.normal, -WorldVector::z()));
+    assert!(hit(&cuboid, WorldVector::zeros(), WorldVector::y()).is_none());
+
+    let cylinder = Cylinder {
+        base: WorldVector::new(0.0, -1.0, 5.0),
+        axis: WorldVector::y(),
+        radius: 1.0,
+        height: 2.0,
+        material: material(),
+    };
+    let wall = hit(&cylinder, WorldVector::zeros(), WorldVector::z()).unwrap();
+    assert!((wall.t - 4.0).abs() < 1e-9 && close(wall.normal, WorldVector::z()));
+    let from_above = WorldVector::new(0.0, 5.0, 5.0);
+    let cap = hit(&cylinder, from_above, from_above - WorldVector::y()).unwrap();
+    assert!((cap.t - 4.0).abs() < 1e-9 && close(cap.normal, -WorldVector::y()));
+    assert!(hit(
+        &cylinder,
+        WorldVector::zeros(),
+        WorldVector::new(0.0, 1.0, 1.0)
+    )
+    .is_none());
+
+    // the nearest of any kind of shape is hit
+    let world = World::new(
+        vec![Box::new(floor), Box::new(cuboid), Box::new(triangle)],
+        vec![],
+        Camera::new(CanvasVector::new(8, 8)),
+        LightColour::from_element(1.0),
+    );
+    let ray = Ray::new(WorldVector::zeros(), WorldVector::z());
+    let (index, nearest) = world.closest_intersection(&ray, 1.0, 10000.0).unwrap();
+    assert_eq!(index, 1);
+    assert!((nearest.t - 4.0).abs() < 1e-9);
+    let ray = Ray::new(WorldVector::zeros(), WorldVector::new(0.0, -1.0, 1.0));
+    assert_eq!(world.closest_intersection(&ray, 1.0, 10000.0).unwrap().0, 0);
+}
+
+#[test]
+fn test_directional_shadows() {
+    let mut material = ambient_sphere(WorldVector::zeros(), LightColour::x(), 0.0).material;
+    material.specular = Some(10.0);
+    let hit = Hit {
+        point: WorldVector::new(0.0, 0.0, 9.0),
+        normal: WorldVector::z(),
+        direction: WorldVector::z(),
+        material,
+    };
+    // a directional light shining along z is blocked by anything
+    // before the hit however far, but not by what's behind it
+    for (center, blocked) in [
+        (WorldVector::new(0.0, 0.0, -100.0), true),
+        (WorldVector::new(0.0, 0.0, 4.5), true),
+        (WorldVector::new(3.0, 0.0, 4.5), false),
+        (WorldVector::new(0.0, 0.0, 14.0), false),
+    ] {
+        let world = World::new(
+            vec![Box::new(Disc {
+                center,
+                normal: WorldVector::z(),
+                radius: 1.0,
+                material: ambient_sphere(center, LightColour::y(), 0.0).material,
+            })],
+            vec![Box::new(DirectionalLight {
+                direction: WorldVector::z(),
+                colour: LightColour::from_element(0.5),
+            })],
+            Camera::new(CanvasVector::new(8, 8)),
+            LightColour::zeros(),
+        );
+        // shadows block the specular highlight
+        let specular = world.get_lighting(&hit).specular;
+        assert_eq!(specular == LightColour::zeros(), blocked, "{center:?}");
+    }
+}
+
+/// A cube of side 2 about the origin, red on the face towards -z
+const CUBE_OBJ: &str = "# cube
+mtllib cube.mtl
+v -1 -1 -1
+v 1 -1 -1
+v 1 1 -1
+v -1 1 -1
+v -1 -1 1
+v 1 -1 1
+v 1 1 1
+v -1 1 1
+vn 0 0 -1
+
+usemtl white
+f 5 6 7 8
+f 1 5 8 4
+f 2 3 7 6
+f 1 2 6 5
+f 4 8 7 3
+usemtl red
+f 1//1 4//1 3//1 2//1
+";
+
+const CUBE_MTL: &str = "newmtl white
+Kd 1 1 1
+newmtl red
+Kd 1 0 0
+Ns 250
+";
+
+/// Opens `cube.mtl` from memory
+fn open_cube_mtl(name: &str) -> Result<Box<dyn std::io::BufRead>, std::io::Error> {
+    match name {
+        "cube.mtl" => Ok(Box::new(CUBE_MTL.as_bytes())),
+        _ => Err(std::io::ErrorKind::NotFound.into()),
+    }
+}
+
+#[test]
+fn test_obj_cube() {
+    let mut meshes = read_obj(&mut CUBE_OBJ.as_bytes(), &mut open_cube_mtl).unwrap();
+    assert_eq!(meshes.len(), 2);
+    let (white, red) = (&meshes[0], &meshes[1]);
+    assert_eq!((white.triangles().len(), red.triangles().len()), (10, 2));
+    assert_eq!(white.material.colour, LightColour::from_element(1.0));
+    assert_eq!(white.material.specular, None);
+    assert_eq!(red.material.colour, LightColour::x());
+    assert_eq!(red.material.specular, Some(250.0));
+
+    for mesh in &mut meshes {
+        mesh.place(1.0, WorldVector::new(0.0, 0.0, 5.0));
+    }
+    let world = World::new(
+        meshes
+            .into_iter()
+            .map(|mesh| Box::new(mesh) as Box<dyn Shape>)
+            .collect(),
+        vec![],
+        Camera::new(CanvasVector::new(8, 8)),
+        LightColour::from_element(1.0),
+    );
+    // the red face is seen first, normals face into the cube
+    let ray = Ray::new(WorldVector::zeros(), WorldVector::new(0.1, 0.2, 1.0));
+    let (index, front) = world.closest_intersection(&ray, 0.0, 10000.0).unwrap();
+    assert_eq!(index, 1);
+    assert!((front.t - 4.0).abs() < 1e-9);
+    assert!((front.normal - WorldVector::z()).norm() < 1e-9);
+
+    let inside = WorldVector::new(0.0, 0.0, 5.0);
+    let ray = Ray::new(inside, inside + WorldVector::new(0.3, -0.2, 1.0));
+    let (index, back) = world.closest_intersection(&ray, 0.0, 10000.0).unwrap();
+    assert_eq!(index, 0);
+    assert!((back.t - 1.0).abs() < 1e-9);
+    assert!((back.normal + WorldVector::z()).norm() < 1e-9);
+}
+
+#[test]
+fn test_obj_smooth_shading() {
+    // normals lean apart across the triangle, negative indices count back
+    let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn -1 0 1\nvn 1 0 1\nvn 0 0 1\nf -3//1 -2//2 -1//3\n";
+    let meshes = read_obj(&mut obj.as_bytes(), &mut open_cube_mtl).unwrap();
+    let mesh = &meshes[0];
+    assert_eq!(mesh.material.colour, LightColour::from_element(0.8));
+
+    let from = WorldVector::new(0.25, 0.25, 5.0);
+    let hit = mesh
+        .intersection(&Ray::new(from, from - WorldVector::z()), 0.0, 10000.0)
+        .unwrap();
+    assert!((hit.t - 5.0).abs() < 1e-9);
+    let expected = -WorldVector::new(-0.25, 0.0, 1.0).normalize();
+    assert!((hit.normal - expected).norm() < 1e-9);
+}
+
+#[test]
+fn test_obj_malformed() {
+    let error = |obj: &str| {
+        let error = read_obj(&mut obj.as_bytes(), &mut open_cube_mtl).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        error.to_string()
+    };
+    assert_eq!(error("v 0 0 0\nv 1 x 0\n"), "line 2: `x` isn't a number");
+    assert_eq!(error("v 0 0\n"), "line 1: expected 3 numbers");
+    assert_eq!(
+        error("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n"),
+        "line 4: index 4 is out of range"
+    );
+    assert_eq!(
+        error("v 0 0 0\nv 1 0 0\nf 1 2\n"),
+        "line 3: a face needs at least 3 vertices"
+    );
+    assert!(read_mtl(&mut "Kd 1 0 0\n".as_bytes()).is_err());
+}
+
+#[test]
+fn test_obj_missing_materials() {
+    // libraries that can't be opened and unknown materials are drawn
+    // in the default material, known ones still apply
+    let obj = "mtllib missing.mtl cube.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\n\
+        usemtl blue\nf 1 2 3\nusemtl red\nf 1 2 3\n";
+    let meshes = read_obj(&mut obj.as_bytes(), &mut open_cube_mtl).unwrap();
+    assert_eq!(meshes.len(), 2);
+    assert_eq!(meshes[0].material.colour, LightColour::from_element(0.8));
+    assert_eq!(meshes[1].material.colour, LightColour::x());
+
+    // a library that's read but malformed is still an error
+    let error = read_obj(&mut "mtllib bad.mtl\n".as_bytes(), &mut |_| {
+        Ok(Box::new("Kd 1 0 0\n".as_bytes()) as Box<dyn std::io::BufRead>)
+    })
+    .unwrap_err();
+    assert_eq!(error.to_string(), "line 1: bad.mtl: line 1: `Kd` before `newmtl`");
+}
+
+#[test]
+fn test_bvh() {
+    // a fixed sequence of numbers in [0, 1)
+    let mut seed = 0x2545F4914F6CDD1Du64;
+    let mut random = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed >> 11) as f64 / (1u64 << 53) as f64
+    };
+    let mut scattered = || {
+        let center = WorldVector::new(random(), random(), random()) * 40.0;
+        ambient_sphere(
+            center - WorldVector::new(20.0, 20.0, -5.0),
+            LightColour::x(),
+            0.0,
+        )
+    };
+    let mut shapes = (0..300)
+        .map(|_| Box::new(scattered()) as Box<dyn Shape>)
+        .collect::<Vec<_>>();
+    shapes.push(Box::new(Plane {
+        point: WorldVector::new(0.0, -15.0, 0.0),
+        normal: WorldVector::y(),
+        material: ambient_sphere(WorldVector::zeros(), LightColour::y(), 0.0).material,
+    }));
+    let moved = (0..50).map(|_| scattered()).collect::<Vec<_>>();
+    let rays = (0..1000)
+        .map(|_| {
+            let direction = WorldVector::new(random() - 0.5, random() - 0.5, 1.0);
+            Ray::new(WorldVector::zeros(), direction)
+        })
+        .collect::<Vec<_>>();
+
+    // the hierarchy finds the same hits as testing every shape in turn
+    let check = |world: &World| {
+        let mut hits = 0;
+        for ray in &rays {
+            let linear = world
+                .shapes()
+                .iter()
+                .enumerate()
+                .filter_map(|(i, shape)| Some((i, shape.intersection(ray, 0.001, 10000.0)?)))
+                .min_by(|(_, a), (_, b)| a.t.total_cmp(&b.t));
+            assert_eq!(world.closest_intersection(ray, 0.001, 10000.0), linear);
+            assert_eq!(world.occluded(ray, 0.001, 10000.0), linear.is_some());
+            hits += linear.is_some() as usize;
+        }
+        // some rays miss everything
+        assert!(hits > 100 && hits < rays.len());
+    };
+    let mut world = World::new(
+        shapes,
+        vec![],
+        Camera::new(CanvasVector::new(8, 8)),
+        LightColour::from_element(1.0),
+    );
+    check(&world);
+
+    // moving shapes refits the hierarchy around them
+    for (i, sphere) in moved.into_iter().enumerate() {
+        world.set_shape(i * 5, Box::new(sphere));
+    }
+    check(&world);
+    world.rebuild();
+    check(&world);
+}
+
+#[test]
+fn test_camera() {
+    let size = CanvasVector::new(16, 9);
+    let mut camera = Camera::new(size);
+    let direction = |ray: Ray| (-ray.direction()).normalize();
+    let center = Vector2::new(8.0, 4.5);
+
+    // the center of the canvas is straight ahead, wherever the camera is
+    let ahead = direction(camera.primary_ray(center, size));
+    assert!((ahead - camera.basis.forward).norm() < 1e-9);
+    camera.position = WorldVector::new(1.0, 2.0, 3.0);
+    camera.look(0.7, -0.3);
+    let ray = camera.primary_ray(center, size);
+    assert_eq!(ray.origin(), camera.position);
+    assert!((direction(ray) - camera.basis.forward).norm() < 1e-9);
+
+    // the edges span the field of view, 90 degrees tall
+    // and as wide as the canvas is relative to its height
+    let (forward, right, up) = (camera.basis.forward, camera.basis.right, camera.basis.up);
+    let half_fov = std::f64::consts::FRAC_PI_4;
+    let top = direction(camera.primary_ray(Vector2::new(8.0, 0.0), size));
+    assert!((top.angle(&forward) - half_fov).abs() < 1e-9);
+    assert!(top.dot(&up) > 0.0 && top.dot(&right).abs() < 1e-9);
+    let left = direction(camera.primary_ray(Vector2::new(0.0, 4.5), size));
+    assert!((left.angle(&forward) - (16f64 / 9.0).atan()).abs() < 1e-9);
+    assert!(left.dot(&right) < 0.0 && left.dot(&up).abs() < 1e-9);
+    let top_left = direction(camera.primary_ray(Vector2::zeros(), size));
+    let bottom_right = direction(camera.primary_ray(size.cast(), size));
+    assert!(((top_left + bottom_right).normalize() - forward).norm() < 1e-9);
+    let diagonal = (1.0 + (16f64 / 9.0).powi(2)).sqrt().atan();
+    assert!((top_left.angle(&forward) - diagonal).abs() < 1e-9);
+
+    // pixels twice as tall as wide, such as with 2:1 terminal
+    // cells, make a square canvas half as wide as it's tall
+    let square = CanvasVector::new(16, 16);
+    camera.set_pixel_aspect(0.5, square);
+    let left = direction(camera.primary_ray(Vector2::new(0.0, 8.0), square));
+    assert!((left.angle(&forward) - 0.5f64.atan()).abs() < 1e-9);
+    let wide = CanvasVector::new(32, 16);
+    camera.resize(wide);
+    let left = direction(camera.primary_ray(Vector2::new(0.0, 8.0), wide));
+    assert!((left.angle(&forward) - half_fov).abs() < 1e-9);
+
+    // offsets within a pixel move the ray across it
+    let top_left = direction(camera.primary_ray(Vector2::new(3.25, 2.25), wide));
+    let bottom_right = direction(camera.primary_ray(Vector2::new(3.75, 2.75), wide));
+    let middle = direction(camera.primary_ray(Vector2::new(3.5, 2.5), wide));
+    assert!((bottom_right - top_left).dot(&right) > 0.0);
+    assert!((bottom_right - top_left).dot(&up) < 0.0);
+    assert!(((top_left + bottom_right).normalize() - middle).norm() < 1e-3);
+
+    // dragging far down stops just short of looking straight down,
+    // however far it goes, the view doesn't flip over
+    let limit = std::f64::consts::FRAC_PI_2 - 0.01;
+    for _ in 0..3 {
+        camera.process_mouse_motion(Vector2::new(0, i16::MAX), 1000.0);
+        assert_eq!(camera.pitch, -limit);
+    }
+    assert!(camera.basis.forward.y < -0.99 && camera.basis.forward.y > -1.0);
+    assert!(camera.basis.up.y > 0.0);
+    camera.process_mouse_motion(Vector2::new(0, i16::MIN), 1000.0);
+    assert_eq!(camera.pitch, limit);
+}
+
+#[test]
+fn test_soft_shadows() {
+    // a ball hangs between a round light and the floor
+    let light = |samples| SphereLight {
+        center: WorldVector::new(0.0, 10.0, 0.0),
+        radius: 2.0,
+        colour: LightColour::from_element(1.0),
+        samples,
+    };
+    let mut ball = ambient_sphere(WorldVector::new(0.0, 5.0, 0.0), LightColour::x(), 0.0);
+    ball.radius = 1.5;
+    let mut world = World::new(
+        vec![Box::new(ball)],
+        vec![Box::new(light(64))],
+        Camera::new(CanvasVector::new(8, 8)),
+        LightColour::zeros(),
+    );
+    let floor = |x: f64| Hit {
+        point: WorldVector::new(x, 0.0, 0.0),
+        normal: -WorldVector::y(),
+        direction: WorldVector::z(),
+        material: ambient_sphere(WorldVector::zeros(), LightColour::y(), 0.0).material,
+    };
+    let diffuse = |world: &World, x: f64| world.get_lighting(&floor(x)).diffuse.x;
+    let unshadowed = |x: f64| light(1).diffuse(floor(x).point, floor(x).normal).x;
+
+    // under the ball none of the light is seen, far from it all of it is,
+    // between the two is a penumbra where some of it is
+    assert_eq!(diffuse(&world, 0.0), 0.0);
+    assert_eq!(diffuse(&world, 8.0), unshadowed(8.0));
+    let penumbra = diffuse(&world, 3.1) / unshadowed(3.1);
+    assert!(penumbra > 0.1 && penumbra < 0.9, "{penumbra}");
+    let further = diffuse(&world, 3.6) / unshadowed(3.6);
+    assert!(further > penumbra, "{further}");
+
+    // a single sample casts hard shadows, the light's center is just hidden
+    world.light_sources = vec![Box::new(light(1))];
+    assert_eq!(diffuse(&world, 3.1), 0.0);
+    assert_eq!(diffuse(&world, 3.6), unshadowed(3.6));
+
+    // a rectangle's samples are spread evenly over it
+    let rectangle = RectangleLight {
+        corner: WorldVector::new(-1.0, 10.0, -1.0),
+        width: WorldVector::new(2.0, 0.0, 0.0),
+        height: WorldVector::new(0.0, 0.0, 2.0),
+        colour: LightColour::from_element(1.0),
+        samples: 4,
+    };
+    let mut targets = (0..rectangle.samples())
+        .map(|sample| rectangle.sample_direction(WorldVector::zeros(), sample))
+        .collect::<Vec<_>>();
+    targets.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.z.total_cmp(&b.z)));
+    assert_eq!(
+        targets,
+        [
+            WorldVector::new(-0.5, 10.0, -0.5),
+            WorldVector::new(-0.5, 10.0, 0.5),
+            WorldVector::new(0.5, 10.0, -0.5),
+            WorldVector::new(0.5, 10.0, 0.5),
+        ]
+    );
+
+    // counts that don't make a square fill a whole grid, so the samples
+    // are centred on the light rather than bunched to one side
+    for (samples, filled) in [(5, 6), (8, 9), (2, 2)] {
+        let rectangle = RectangleLight {
+            samples,
+            ..rectangle
+        };
+        assert_eq!(rectangle.samples(), filled);
+        let mean = (0..filled)
+            .map(|sample| rectangle.sample_direction(WorldVector::zeros(), sample))
+            .sum::<WorldVector>()
+            / filled as f64;
+        assert!(
+            (mean - WorldVector::new(0.0, 10.0, 0.0)).norm() < 1e-9,
+            "{samples}: {mean:?}"
+        );
+    }
+}
+
+#[test]
+fn test_spot_light() {
+    // a spot light above the floor shines straight down
+    let spot = SpotLight {
+        position: WorldVector::new(0.0, 10.0, 0.0),
+        direction: -WorldVector::y(),
+        inner_angle: 0.2,
+        outer_angle: 0.4,
+        colour: LightColour::from_element(1.0),
+        attenuation: Attenuation::NONE,
+    };
+    let floor = -WorldVector::y();
+    let diffuse = |spot: &SpotLight, x: f64| spot.diffuse(WorldVector::new(x, 0.0, 0.0), floor).x;
+    let point = |x: f64| {
+        PointLight {
+            position: spot.position,
+            colour: spot.colour,
+            attenuation: Attenuation::NONE,
+        }
+        .diffuse(WorldVector::new(x, 0.0, 0.0), floor)
+        .x
+    };
+
+    // inside the inner cone it's as bright as a point light,
+    // outside the outer one it's dark, and it fades between them
+    assert_eq!(diffuse(&spot, 0.0), 1.0);
+    assert_eq!(diffuse(&spot, 1.5), point(1.5));
+    assert_eq!(diffuse(&spot, 5.0), 0.0);
+    let edge = diffuse(&spot, 3.0) / point(3.0);
+    assert!(edge > 0.0 && edge < 1.0, "{edge}");
+    assert!(diffuse(&spot, 2.5) / point(2.5) > edge);
+
+    // attenuated light fades with distance
+    let faded = SpotLight {
+        attenuation: Attenuation {
+            constant: 1.0,
+            linear: 0.1,
+            quadratic: 0.01,
+        },
+        ..spot
+    };
+    assert_eq!(diffuse(&faded, 0.0), 1.0 / (1.0 + 1.0 + 1.0));
+    assert_eq!(Attenuation::default().at(100.0), 1.0);
+
+    // its shadow rays run to its position like a point light's
+    assert_eq!(
+        spot.light_direction(WorldVector::zeros()),
+        WorldVector::new(0.0, 10.0, 0.0)
+    );
+    assert_eq!(spot.t_max(), 1.0);
+}
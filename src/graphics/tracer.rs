@@ -0,0 +1,197 @@
+use std::{
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+use nalgebra::Vector2;
+
+use super::{Canvas, CanvasVector, Colour, Renderer, World};
+
+/// Traces frames of a world, rows of the frame are shared
+/// between a pool of worker threads
+pub struct Tracer {
+    pool: Pool,
+    /// Times reflected and refracted rays are followed
+    reflection_depth: u32,
+    /// The last traced frame, row by row
+    pixels: Vec<Colour>,
+}
+
+impl Tracer {
+    /// Reflections and refractions followed unless set otherwise
+    pub const DEFAULT_REFLECTION_DEPTH: u32 = 3;
+
+    /// Creates a tracer using `threads` threads, the calling thread
+    /// and `threads - 1` workers, 1 traces on the calling thread
+    pub fn new(threads: usize) -> Self {
+        assert!(threads > 0, "a tracer needs at least one thread");
+        Self {
+            pool: Pool::new(threads - 1),
+            reflection_depth: Self::DEFAULT_REFLECTION_DEPTH,
+            pixels: Vec::new(),
+        }
+    }
+
+    /// Creates a tracer with a thread per available core
+    pub fn available() -> Self {
+        Self::new(thread::available_parallelism().map_or(1, NonZeroUsize::get))
+    }
+
+    #[inline]
+    pub fn threads(&self) -> usize {
+        self.pool.workers.len() + 1
+    }
+
+    /// Sets how many times reflected and refracted rays are
+    /// followed, 0 draws mirrors and glass in their own colour only
+    pub fn set_reflection_depth(&mut self, depth: u32) {
+        self.reflection_depth = depth;
+    }
+
+    /// Traces a frame of `size` pixels, returns its pixels row by row
+    pub fn trace(&mut self, world: &World, size: CanvasVector) -> &[Colour] {
+        let depth = self.reflection_depth;
+        self.pixels.resize(size.x * size.y, Colour::zeros());
+        if self.pool.workers.is_empty() || size.y < 2 {
+            for (y, row) in self.pixels.chunks_mut(size.x).enumerate() {
+                trace_row(world, y, row, size, depth);
+            }
+            return &self.pixels;
+        }
+
+        // threads take the next untraced row, so rows that are slower
+        // to trace don't leave the other threads idle. Each row is
+        // borrowed by a single thread, so no pixel is written twice.
+        let rows = Mutex::new(self.pixels.chunks_mut(size.x).enumerate());
+        self.pool.run(&|| loop {
+            let next = rows.lock().unwrap().next();
+            match next {
+                Some((y, row)) => trace_row(world, y, row, size, depth),
+                None => break,
+            }
+        });
+        &self.pixels
+    }
+}
+
+impl Renderer for Tracer {
+    fn render(&mut self, world: &World, canvas: &mut dyn Canvas) {
+        let size = canvas.size();
+        self.trace(world, size);
+        for (y, row) in self.pixels.chunks(size.x).enumerate() {
+            for (x, &colour) in row.iter().enumerate() {
+                canvas.put_pixel(colour, CanvasVector::new(x, y));
+            }
+        }
+    }
+
+    fn name(&self) -> String {
+        format!(
+            "tracer, {} threads, {} reflections",
+            self.threads(),
+            self.reflection_depth
+        )
+    }
+}
+
+/// A job every thread of the pool runs once per frame
+type Job<'a> = dyn Fn() + Sync + 'a;
+
+/// A worker thread, waits for jobs for as long as the pool lives
+struct Worker {
+    jobs: Sender<&'static Job<'static>>,
+    thread: JoinHandle<()>,
+}
+
+/// Worker threads kept alive between frames, so threads
+/// aren't spawned for every frame
+struct Pool {
+    workers: Vec<Worker>,
+    /// Whether each worker finished its job without panicking
+    done: Receiver<bool>,
+}
+
+impl Pool {
+    fn new(workers: usize) -> Self {
+        let (finished, done) = mpsc::channel();
+        let workers = (0..workers)
+            .map(|i| {
+                let (jobs, queued) = mpsc::channel::<&'static Job<'static>>();
+                let finished = finished.clone();
+                let thread = thread::Builder::new()
+                    .name(format!("tracer {}", i + 1))
+                    .spawn(move || {
+                        for job in queued {
+                            let ok = panic::catch_unwind(AssertUnwindSafe(job)).is_ok();
+                            if finished.send(ok).is_err() {
+                                break;
+                            }
+                        }
+                    })
+                    .expect("failed to spawn a tracer thread");
+                Worker { jobs, thread }
+            })
+            .collect();
+        Self { workers, done }
+    }
+
+    /// Runs `job` on every worker and the calling thread, returns once
+    /// all of them have finished, panics if any of them panicked
+    fn run(&self, job: &Job<'_>) {
+        // SAFETY: the job outlives every use of it, each worker it is sent
+        // to reports back below before returning, even when the job panics
+        let job = unsafe { std::mem::transmute::<&Job<'_>, &'static Job<'static>>(job) };
+        let sent = self
+            .workers
+            .iter()
+            .filter(|worker| worker.jobs.send(job).is_ok())
+            .count();
+
+        let caller = panic::catch_unwind(AssertUnwindSafe(job));
+        let finished = self.done.iter().take(sent).filter(|&ok| ok).count();
+        if let Err(panic) = caller {
+            panic::resume_unwind(panic);
+        }
+        assert_eq!(finished, sent, "a tracer thread panicked");
+    }
+}
+
+impl Drop for Pool {
+    fn drop(&mut self) {
+        // closing a worker's jobs ends its thread
+        for worker in self.workers.drain(..) {
+            drop(worker.jobs);
+            let _ = worker.thread.join();
+        }
+    }
+}
+
+/// Traces the colour of the pixel at `position` on a canvas of `size`,
+/// following reflected and refracted rays up to `depth` times
+pub fn trace_pixel(
+    world: &World,
+    position: CanvasVector,
+    size: CanvasVector,
+    depth: u32,
+) -> Colour {
+    // through the pixel's center
+    let ray = world
+        .camera
+        .primary_ray(position.cast() + Vector2::repeat(0.5), size);
+    match world.cast_ray(&ray, 1f64, 10000f64) {
+        Some(hit) => world.compute_lighting(&hit, depth),
+        None => Colour::zeros(),
+    }
+}
+
+#[inline]
+fn trace_row(world: &World, y: usize, row: &mut [Colour], size: CanvasVector, depth: u32) {
+    for (x, pixel) in row.iter_mut().enumerate() {
+        *pixel = trace_pixel(world, CanvasVector::new(x, y), size, depth);
+    }
+}
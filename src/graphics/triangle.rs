@@ -0,0 +1,55 @@
+use super::{facing, Aabb, Intersection, Material, Ray, Shape, WorldVector};
+
+/// A flat triangle seen from both sides
+#[derive(Debug)]
+pub struct Triangle {
+    pub vertices: [WorldVector; 3],
+    pub material: Material,
+}
+
+impl Shape for Triangle {
+    fn intersection(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Intersection> {
+        let (t, _, _) = barycentric(&self.vertices, ray)?;
+        let [a, b, c] = self.vertices;
+        (t > t_min && t < t_max).then(|| Intersection {
+            t,
+            normal: facing((b - a).cross(&(c - a)).normalize(), -ray.direction()),
+        })
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(Aabb::around(self.vertices))
+    }
+}
+
+/// Returns where `ray` crosses the triangle, and the barycentric
+/// coordinates `u` and `v` of the hit, weighting the second and
+/// third vertices
+#[inline]
+pub(super) fn barycentric(vertices: &[WorldVector; 3], ray: &Ray) -> Option<(f64, f64, f64)> {
+    // Möller-Trumbore
+    let [a, b, c] = *vertices;
+    let (edge1, edge2) = (b - a, c - a);
+    let direction = -ray.direction();
+    let p = direction.cross(&edge2);
+    let determinant = edge1.dot(&p);
+    // parallel to the triangle
+    if determinant.abs() < f64::EPSILON {
+        return None;
+    }
+    let s = ray.origin() - a;
+    let u = s.dot(&p) / determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(&edge1);
+    let v = direction.dot(&q) / determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    Some((edge2.dot(&q) / determinant, u, v))
+}
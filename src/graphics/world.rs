@@ -1,7 +1,10 @@
 use nalgebra::clamp;
 use num_traits::clamp_min;
 
-use super::{Camera, Colour, LightColour, LightSource, Material, Ray, Sphere, WorldVector};
+use super::{
+    Aabb, Bvh, Camera, Colour, Intersection, LightColour, LightSource, Material, Ray, Shape,
+    WorldVector,
+};
 
 /// Defines a ray hit, what point it hit,
 /// the normal of the point and what material
@@ -36,64 +39,163 @@ impl LightingContribution {
 /// Manages the objects within the world and
 /// the light within the world
 pub struct World {
-    pub spheres: Vec<Sphere>,
+    shapes: Vec<Box<dyn Shape>>,
+    /// Finds which shapes a ray might hit, kept in step with `shapes`
+    bvh: Bvh,
     pub light_sources: Vec<Box<dyn LightSource>>,
     pub camera: Camera,
     pub ambient: LightColour,
 }
 
 impl World {
-    pub fn closest_intersection(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<(usize, f64)> {
-        let mut closest_t: f64 = f64::MAX;
-        let mut closest_sphere_index: Option<usize> = None;
-        for (i, sphere) in self.spheres.iter().enumerate() {
-            match sphere.intersect(&ray) {
-                Some((t1, t2)) => {
-                    if t1 < t_max && t1 < closest_t && t1 > t_min {
-                        closest_t = t1;
-                        closest_sphere_index = Some(i);
-                    }
-                    if t2 < t_max && t2 < closest_t && t2 > t_min {
-                        closest_t = t2;
-                        closest_sphere_index = Some(i);
-                    }
-                }
-                None => {}
-            }
+    pub fn new(
+        shapes: Vec<Box<dyn Shape>>,
+        light_sources: Vec<Box<dyn LightSource>>,
+        camera: Camera,
+        ambient: LightColour,
+    ) -> Self {
+        let mut this = Self {
+            shapes,
+            bvh: Bvh::default(),
+            light_sources,
+            camera,
+            ambient,
+        };
+        this.rebuild();
+        this
+    }
+
+    pub fn shapes(&self) -> &[Box<dyn Shape>] {
+        &self.shapes
+    }
+
+    /// Adds a shape to the world, rebuilding the hierarchy
+    pub fn add_shape(&mut self, shape: Box<dyn Shape>) {
+        self.shapes.push(shape);
+        self.rebuild();
+    }
+
+    /// Replaces the shape at `index`, such as with a moved copy of it.
+    /// The hierarchy is refit around it, `rebuild` when shapes have
+    /// moved far.
+    pub fn set_shape(&mut self, index: usize, shape: Box<dyn Shape>) {
+        // a shape gaining or losing its bounds moves between the
+        // hierarchy and the shapes tested against every ray
+        let rebuild = shape.bounds().is_some() != self.shapes[index].bounds().is_some();
+        self.shapes[index] = shape;
+        if rebuild {
+            self.rebuild();
+        } else {
+            self.bvh.refit(&self.bounds());
         }
-        closest_sphere_index.map(|sphere_index| (sphere_index, closest_t))
     }
 
-    pub fn trace_ray(&self, through: WorldVector, t_min: f64, t_max: f64) -> Option<Hit> {
-        let ray = Ray::new(self.camera.position, through);
-        let intersection = self.closest_intersection(&ray, t_min, t_max);
+    /// Rebuilds the hierarchy from scratch, so it fits the shapes best
+    pub fn rebuild(&mut self) {
+        self.bvh = Bvh::build(&self.bounds());
+    }
+
+    fn bounds(&self) -> Vec<Option<Aabb>> {
+        self.shapes.iter().map(|shape| shape.bounds()).collect()
+    }
+
+    /// Returns the index of the shape `ray` first hits, and where
+    pub fn closest_intersection(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<(usize, Intersection)> {
+        self.bvh.closest(ray, t_min, t_max, |i, t_max| {
+            let intersection = self.shapes[i].intersection(ray, t_min, t_max)?;
+            Some((intersection.t, intersection))
+        })
+    }
 
-        match intersection {
-            Some((sphere_index, t)) => {
-                let sphere = &self.spheres[sphere_index];
+    /// Whether `ray` hits any shape between `t_min` and `t_max`
+    pub fn occluded(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.bvh.any(ray, t_min, t_max, |i| {
+            self.shapes[i].intersection(ray, t_min, t_max).is_some()
+        })
+    }
 
-                let point = ray.point(t);
-                let normal = (sphere.center - point).normalize();
+    /// Returns where `ray` first hits an object, if it does
+    pub fn cast_ray(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+        let (shape_index, intersection) = self.closest_intersection(ray, t_min, t_max)?;
 
-                Some(Hit {
-                    point,
-                    normal,
-                    direction: -ray.direction(),
-                    material: sphere.material.clone(),
-                })
-            }
-            None => None,
-        }
+        Some(Hit {
+            point: ray.point(intersection.t),
+            normal: intersection.normal,
+            direction: -ray.direction(),
+            material: self.shapes[shape_index].material().clone(),
+        })
     }
-    pub fn compute_lighting(&self, hit: &Hit) -> Colour {
-        let lighting = self.get_lighting(hit).total();
-        lighting
-            .component_mul(&hit.material.colour)
+    /// Returns the colour seen at a hit, reflected and
+    /// refracted rays are followed up to `depth` times
+    pub fn compute_lighting(&self, hit: &Hit, depth: u32) -> Colour {
+        self.shade(hit, depth)
             .scale(255.0)
             .try_cast::<u8>()
             .unwrap()
     }
 
+    /// Returns the light `ray` sees, black if it hits nothing, reflected
+    /// and refracted rays are followed up to `depth` times
+    pub fn trace(&self, ray: &Ray, t_min: f64, t_max: f64, depth: u32) -> LightColour {
+        match self.cast_ray(ray, t_min, t_max) {
+            Some(hit) => self.shade(&hit, depth),
+            None => LightColour::zeros(),
+        }
+    }
+
+    /// Light leaving a hit towards the viewer, the lit colour of the
+    /// material blended with what it reflects and what passes through it.
+    /// Transparent objects still cast full shadows.
+    fn shade(&self, hit: &Hit, depth: u32) -> LightColour {
+        let material = &hit.material;
+        let local = self
+            .get_lighting(hit)
+            .total()
+            .component_mul(&material.colour);
+        if depth == 0 || (material.reflective <= 0.0 && material.transparency <= 0.0) {
+            return local;
+        }
+        let direction = reflect(hit.direction, hit.normal);
+        let reflected = self.trace(
+            &Ray::new(hit.point, hit.point + direction),
+            0.001,
+            f64::MAX,
+            depth - 1,
+        );
+        let surface = local * (1.0 - material.reflective) + reflected * material.reflective;
+        if material.transparency <= 0.0 {
+            return surface;
+        }
+
+        // normals face into shapes, so rays along them are entering
+        let (normal, from, to) = if hit.direction.dot(&hit.normal) > 0.0 {
+            (-hit.normal, 1.0, material.refractive_index)
+        } else {
+            (hit.normal, material.refractive_index, 1.0)
+        };
+        let cos_incident = -hit.direction.normalize().dot(&normal.normalize());
+        let fresnel = schlick(cos_incident, from, to);
+        let transmitted = match refract(hit.direction, normal, from / to) {
+            Some(direction) => self
+                .trace(
+                    &Ray::new(hit.point, hit.point + direction),
+                    0.001,
+                    f64::MAX,
+                    depth - 1,
+                )
+                .component_mul(&material.colour),
+            // totally internally reflected, `fresnel` is 1
+            None => LightColour::zeros(),
+        };
+        surface * (1.0 - material.transparency)
+            + (reflected * fresnel + transmitted * (1.0 - fresnel)) * material.transparency
+    }
+
     pub fn get_lighting(&self, hit: &Hit) -> LightingContribution {
         let mut lighting = LightingContribution {
             ambient: self.ambient,
@@ -101,24 +203,30 @@ impl World {
             specular: LightColour::zeros(),
         };
         for light_source in &self.light_sources {
-            // diffuse lighting always applies
-            lighting.diffuse += light_source.diffuse(hit.point, hit.normal);
-
-            // shadows
-            if self
-                .closest_intersection(
-                    &Ray::new(hit.point, light_source.light_direction(hit.point)),
-                    0.001,
-                    light_source.t_max(),
-                )
-                .is_some()
-            {
+            // shadows, rays run from the point towards points over the light,
+            // the light is dimmed by the share of them that are blocked
+            let samples = light_source.samples();
+            let unblocked = (0..samples)
+                .filter(|&sample| {
+                    let direction = light_source.sample_direction(hit.point, sample);
+                    !self.occluded(
+                        &Ray::new(hit.point, hit.point + direction),
+                        0.001,
+                        light_source.t_max(),
+                    )
+                })
+                .count();
+            if unblocked == 0 {
                 continue;
             }
+            let visible = unblocked as f64 / samples as f64;
+
+            lighting.diffuse += light_source.diffuse(hit.point, hit.normal) * visible;
             // apply specular if material has it
             if let Some(specular_exponent) = hit.material.specular {
                 lighting.specular +=
                     light_source.specular(hit.direction, hit.point, hit.normal, specular_exponent)
+                        * visible
             };
         }
         lighting.specular = clamp_min(lighting.specular, LightColour::zeros());
@@ -126,3 +234,42 @@ impl World {
         lighting
     }
 }
+
+/// Reflects `direction` about `normal`, the result is normalised
+#[inline]
+pub fn reflect(direction: WorldVector, normal: WorldVector) -> WorldVector {
+    let normal = normal.normalize();
+    (direction - 2.0 * direction.dot(&normal) * normal).normalize()
+}
+
+/// Refracts `direction` through a surface whose `normal` faces against it,
+/// `eta` is the ratio of the refractive indices, from over to. The result
+/// is normalised, `None` when the ray is totally internally reflected.
+pub fn refract(direction: WorldVector, normal: WorldVector, eta: f64) -> Option<WorldVector> {
+    let (direction, normal) = (direction.normalize(), normal.normalize());
+    let cos_incident = -direction.dot(&normal);
+    // Snell's law, sin t = eta sin i
+    let sin2_transmitted = eta * eta * (1.0 - cos_incident * cos_incident);
+    if sin2_transmitted > 1.0 {
+        return None;
+    }
+    let cos_transmitted = (1.0 - sin2_transmitted).sqrt();
+    Some((eta * direction + (eta * cos_incident - cos_transmitted) * normal).normalize())
+}
+
+/// Schlick's approximation of the fraction of light reflected at a surface
+/// between refractive indices `from` and `to`, 1 when totally internally reflected
+pub fn schlick(cos_incident: f64, from: f64, to: f64) -> f64 {
+    let r0 = ((from - to) / (from + to)).powi(2);
+    // leaving the denser medium the transmitted angle is the larger
+    let cos = if from > to {
+        let sin2_transmitted = (from / to).powi(2) * (1.0 - cos_incident * cos_incident);
+        if sin2_transmitted > 1.0 {
+            return 1.0;
+        }
+        (1.0 - sin2_transmitted).sqrt()
+    } else {
+        cos_incident
+    };
+    r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+}
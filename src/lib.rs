@@ -0,0 +1,6 @@
+#![allow(incomplete_features)] // reason: generic_const_exprs
+#![feature(generic_const_exprs, iter_array_chunks)]
+//! The graphics of the tracer as a library, so it can be rendered,
+//! tested and benched without the app or a terminal
+
+pub mod graphics;
@@ -1,7 +1,6 @@
 #![allow(incomplete_features)] // reason: generic_const_exprs
 #![feature(
     generic_const_exprs,
-    iter_array_chunks,
     ascii_char,
     trait_alias,
     duration_millis_float
@@ -9,16 +8,29 @@
 
 use std::io;
 
-use app::{App, Application};
+use app::{argument, benchmark_app::BenchmarkApp, flag, App, Application};
+use graphics::{Ascii, Braille, HalfBlocks, Quadrants};
+use term_gfx_rs::graphics;
 mod app;
-mod graphics;
 
 fn main() -> Result<(), io::Error> {
     let thread = std::thread::Builder::new()
         .name("main".to_string())
         .stack_size(32 * 1024 * 1024); // 32MiB stack-size
 
-    let handler = thread.spawn(run::<App>)?;
+    // `--benchmark` runs a scripted scene and reports frame timings
+    if flag("--benchmark") {
+        return thread.spawn(run::<BenchmarkApp>)?.join().unwrap();
+    }
+
+    // how pixels are drawn, `--mode half-blocks|quadrants|braille|ascii`
+    let handler = match argument("--mode").as_deref() {
+        Some("half-blocks") => thread.spawn(run::<App<HalfBlocks>>)?,
+        Some("quadrants") => thread.spawn(run::<App<Quadrants>>)?,
+        Some("braille") => thread.spawn(run::<App<Braille>>)?,
+        Some("ascii") => thread.spawn(run::<App<Ascii>>)?,
+        _ => thread.spawn(run::<App>)?,
+    };
 
     handler.join().unwrap()
 }